@@ -1,61 +1,1401 @@
-use crate::scramble::{Puzzle, Scrambler};
+use crate::command::{self, Command, ExportFormat};
+use crate::compare::{self, Side};
+use crate::config::Config;
+use crate::fmc;
+use crate::keys::{Action, Keymap};
+use crate::mbld;
+use crate::query::Query;
+use crate::report::{self, DaySummary, Forecast};
+use crate::scramble::{Puzzle, ScrambleFilter, Scrambler};
 use crate::session::Session;
-use crate::solve::Solve;
+use crate::solve::{Penalty, Solve, SPLIT_PHASES};
+use crate::solver::{self, CrossAnalysis};
+use crate::stats::SessionStats;
+use crate::stackmat::{Packet, Status};
 use crate::storage::Storage;
-use crate::timer::{Timer, TimerState};
+use crate::theme::Theme;
+use crate::timer::{self, Clock, SystemClock, Timer, TimerState};
+use crate::ui::{self, LayoutPreset, TimerDisplay};
+use crate::wca::{RankIndex, Records};
 use crate::Result;
 
+use std::{path::Path, sync::mpsc::{self, Receiver}, thread, time::{Duration, Instant}};
+use chrono::Local;
 use ratatui::crossterm::event::KeyCode;
 
+pub enum View {
+    Timer,
+    SolveDetail { index: usize, analysis: Analysis, tagging: bool },
+    // Worked out once when opened, as the solves cannot change meanwhile.
+    Report { summaries: Vec<DaySummary>, forecast: Option<Forecast> },
+    Compare { sides: [Side; 2], focus: usize, editing: bool },
+    Rankings,
+    Profile,
+    // Drawn over the timer screen.
+    Help
+}
+
+// The X-cross search can take a while, so the solve detail works out its
+// analysis on another thread and picks it up once it is done.
+pub enum Analysis {
+    Unavailable,
+    Pending(Receiver<CrossAnalysis>),
+    Done(CrossAnalysis)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MultiBldStage {
+    Declaring,
+    Ready { cubes: u32 },
+    Entering { cubes: u32, time: Duration }
+}
+
 pub struct App {
     pub should_quit: bool,
     pub timer: Timer,
-    pub session: Session,
+    pub sessions: Vec<Session>,
+    pub current_session: usize,
     pub storage: Box<dyn Storage>,
     pub current_scramble: Option<String>, 
+    pub current_cross_length: Option<usize>,
+    pub scramble_filter: ScrambleFilter,
+    pub split_mode: bool,
+    pub manual_entry: bool,
+    pub target_entry: bool,
+    pub filter: Option<Query>,
+    // Stats over the filtered solves, worked out again only when the filter
+    // or the solves change.
+    filtered_stats: Option<SessionStats>,
+    pub filter_entry: bool,
+    pub command_entry: bool,
+    pub timer_display: TimerDisplay,
+    pub layout: LayoutPreset,
+    pub keymap: Keymap,
+    pub theme: Theme,
+    // Whether timed solves start with WCA inspection, when it started, and
+    // the penalty it gave the solve being timed.
+    pub inspection: bool,
+    pub inspection_start: Option<Instant>,
+    pub inspection_penalty: Option<Penalty>,
+    pub view: View,
+    pub input: String,
+    pub message: Option<String>,
+    pub multi_bld_stage: MultiBldStage,
+    pub stackmat_status: Option<Status>,
+    // Loaded from storage the first time the rankings are shown.
+    pub rank_index: Option<RankIndex>,
+    pub country: Option<String>,
+    // Kinch reference results used in place of the imported world records.
+    pub records: Records,
 }
 
 impl App {
-    pub fn build(storage: Box<dyn Storage>) -> Result<App> {
-        let session = storage.load_session()?;
+    pub fn build(storage: Box<dyn Storage>, config: Config) -> Result<App> {
+        Self::build_with_clock(storage, config, Box::new(SystemClock))
+    }
+
+    pub fn build_with_clock(storage: Box<dyn Storage>, config: Config, clock: Box<dyn Clock>) -> Result<App> {
+        let mut sessions = storage.load_sessions()?;
+        if sessions.is_empty() {
+            sessions.push(Session::new(config.default_puzzle));
+        }
 
-        Ok(App {
+        let mut app = App {
             storage,
-            session,
-            timer: Timer::new(),
+            sessions,
+            current_session: 0,
+            timer: Timer::new(clock),
             should_quit: false,
-            current_scramble: Some(Scrambler::new_scramble(Puzzle::Cube3x3))
-        })
+            current_scramble: None,
+            current_cross_length: None,
+            scramble_filter: ScrambleFilter::default(),
+            split_mode: false,
+            manual_entry: false,
+            target_entry: false,
+            filter: None,
+            filtered_stats: None,
+            filter_entry: false,
+            command_entry: false,
+            timer_display: config.timer_display,
+            layout: config.layout,
+            keymap: config.keymap,
+            theme: config.theme,
+            inspection: config.inspection,
+            inspection_start: None,
+            inspection_penalty: None,
+            view: View::Timer,
+            input: String::new(),
+            message: None,
+            multi_bld_stage: MultiBldStage::Declaring,
+            stackmat_status: None,
+            rank_index: None,
+            country: config.country,
+            records: config.records,
+        };
+        app.switch_puzzle(config.default_puzzle);
+
+        Ok(app)
+    }
+
+    pub fn session(&self) -> &Session {
+        &self.sessions[self.current_session]
+    }
+
+    // World records from the imported WCA export, overridden by any set.
+    pub fn reference_records(&self) -> Records {
+        let records = self.rank_index.as_ref().map(RankIndex::records).unwrap_or_default();
+        records.with_overrides(&self.records)
+    }
+
+    // A damaged index is only reported, leaving the views without rankings.
+    fn load_rank_index(&mut self) {
+        if self.rank_index.is_none() {
+            match self.storage.load_rank_index() {
+                Ok(index) => self.rank_index = index,
+                Err(error) => self.message = Some(error.to_string()),
+            }
+        }
+    }
+
+    pub fn matches_filter(&self, puzzle: Puzzle, solve: &Solve) -> bool {
+        self.filter.as_ref().is_none_or(|query| query.matches(puzzle, solve, &Local))
+    }
+
+    // The solves that stats, charts and exports work on: the current
+    // session's, or those of every matching session when the filter names
+    // a puzzle.
+    pub fn filtered_solves(&self) -> impl DoubleEndedIterator<Item = &Solve> + '_ {
+        let all_sessions = self.filter.as_ref().and_then(Query::puzzle).is_some();
+        self.sessions.iter()
+            .enumerate()
+            .filter(move |(i, _)| all_sessions || *i == self.current_session)
+            .flat_map(move |(_, session)| session.solves().iter().filter(move |solve| self.matches_filter(session.puzzle(), solve)))
+    }
+
+    // The stats of the filtered solves, which are the session's own when
+    // there is no filter.
+    pub fn stats(&self) -> &SessionStats {
+        self.filtered_stats.as_ref().unwrap_or(self.session().stats())
+    }
+
+    pub fn set_filter(&mut self, filter: Option<Query>) {
+        self.filter = filter;
+        self.refresh_filtered_stats();
+    }
+
+    // Worked out again from every solve when the filter, the session or an
+    // existing solve changes.
+    fn refresh_filtered_stats(&mut self) {
+        self.filtered_stats = self.filter.is_some().then(|| SessionStats::from_solves(self.filtered_solves()));
+    }
+
+    // A new solve is added to the filtered stats like the session's own, if
+    // it matches.
+    fn push_filtered_stats(&mut self) {
+        let session = &self.sessions[self.current_session];
+        let Some(solve) = session.solves().last() else { return };
+        if let Some(stats) = &mut self.filtered_stats {
+            if self.filter.as_ref().is_some_and(|query| query.matches(session.puzzle(), solve, &Local)) {
+                stats.push(solve);
+            }
+        }
+    }
+
+    // The puzzle whose solves are shown: the one the filter names, or else
+    // the session's.
+    pub fn shown_puzzle(&self) -> Puzzle {
+        self.filter.as_ref().and_then(Query::puzzle).unwrap_or(self.session().puzzle())
+    }
+
+    // The session's target only applies to solves of its own puzzle.
+    pub fn shown_target(&self) -> Option<Duration> {
+        self.session().target().filter(|_| self.shown_puzzle() == self.session().puzzle())
+    }
+
+    // `at` is when the key was pressed, which timing uses rather than when
+    // the key is handled.
+    pub fn on_key_pressed(&mut self, code: KeyCode, at: Instant) -> Result<()> {
+        match self.view {
+            View::Timer => self.on_timer_key(code, at),
+            View::SolveDetail { index, tagging: true, .. } => self.on_tag_key(code, index),
+            View::SolveDetail { index, .. } => {
+                self.on_detail_key(code, index);
+                Ok(())
+            },
+            View::Report { .. } => self.on_report_key(code),
+            View::Compare { .. } => {
+                self.on_compare_key(code);
+                Ok(())
+            },
+            View::Rankings => {
+                if code == KeyCode::Esc || self.keymap.action(code) == Some(Action::Rankings) {
+                    self.view = View::Timer;
+                }
+                Ok(())
+            },
+            View::Profile => {
+                if code == KeyCode::Esc || self.keymap.action(code) == Some(Action::Profile) {
+                    self.view = View::Timer;
+                }
+                Ok(())
+            },
+            View::Help => {
+                if code == KeyCode::Esc || self.keymap.action(code) == Some(Action::Help) {
+                    self.view = View::Timer;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // Called every frame for what happens without a key press: a fewest
+    // moves attempt ends when its hour is up, a multi-blind attempt stops at
+    // its time limit to have its result entered, and a solve's cross
+    // analysis arrives.
+    pub fn on_tick(&mut self) -> Result<()> {
+        if let View::SolveDetail { analysis, .. } = &mut self.view {
+            if let Analysis::Pending(receiver) = analysis {
+                match receiver.try_recv() {
+                    Ok(done) => *analysis = Analysis::Done(done),
+                    Err(mpsc::TryRecvError::Disconnected) => *analysis = Analysis::Unavailable,
+                    Err(mpsc::TryRecvError::Empty) => {},
+                }
+            }
+        }
+
+        let running = matches!(self.timer.state(), TimerState::Running { .. });
+        if running && self.session().puzzle() == Puzzle::Cube3x3Fmc && self.timer.elapsed() >= fmc::TIME_LIMIT {
+            self.submit_solution()?;
+        }
+        if let (true, MultiBldStage::Ready { cubes }) = (running, self.multi_bld_stage) {
+            let limit = mbld::time_limit(cubes);
+            if self.session().puzzle() == Puzzle::MultiBld && self.timer.elapsed() >= limit {
+                self.timer.pause();
+                self.multi_bld_stage = MultiBldStage::Entering { cubes, time: limit };
+                self.message = Some("Time is up".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    // A connected Stackmat drives the timer instead of the space bar, and its
+    // final time is the one recorded.
+    pub fn on_stackmat_packet(&mut self, packet: Packet) -> Result<()> {
+        let previous = self.stackmat_status.replace(packet.status);
+        if !matches!(self.view, View::Timer) || self.manual_entry || !self.session().puzzle().is_timed() {
+            return Ok(());
+        }
+
+        match (previous, packet.status) {
+            (Some(Status::Running), Status::Running) => {},
+            (_, Status::Running) => {
+                self.message = None;
+                self.end_inspection(self.timer.now());
+                self.timer.reset();
+                self.timer.start();
+            },
+            (Some(Status::Running), Status::Stopped) => {
+                self.timer.pause();
+
+                let solve = self.build_timed_solve(packet.time)?;
+                self.record_solve(solve)?;
+            },
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn on_timer_key(&mut self, code: KeyCode, at: Instant) -> Result<()> {
+        let running = matches!(self.timer.state(), TimerState::Running { .. });
+        if running && self.session().puzzle() == Puzzle::Cube3x3Fmc {
+            return self.on_solution_key(code);
+        }
+        if self.target_entry {
+            self.on_target_key(code);
+            return Ok(());
+        }
+        if self.filter_entry {
+            self.on_filter_key(code);
+            return Ok(());
+        }
+        if self.command_entry {
+            return self.on_command_key(code);
+        }
+        if self.session().puzzle() == Puzzle::MultiBld && self.on_multi_bld_key(code, running, at)? {
+            return Ok(());
+        }
+        if self.manual_entry && self.on_manual_key(code)? {
+            return Ok(());
+        }
+
+        let action = self.keymap.action(code);
+        if self.inspection_start.is_some() && action != Some(Action::StartStop) {
+            if code == KeyCode::Esc {
+                self.inspection_start = None;
+            }
+            return Ok(());
+        }
+
+        match action {
+            Some(Action::Quit) => return self.run_command(Command::Quit),
+            Some(Action::StartStop) => {
+                if running && self.split_mode && self.timer.splits().len() + 1 < SPLIT_PHASES.len() {
+                    self.timer.split_at(at);
+                } else if running {
+                    self.timer.pause_at(at);
+
+                    let mut solve = self.build_timed_solve(self.timer.elapsed())?;
+                    solve.set_splits(self.timer.splits().to_vec());
+                    self.record_solve(solve)?;
+                } else if self.inspection && self.inspection_start.is_none() && !self.session().puzzle().is_blindfolded() {
+                    self.message = None;
+                    self.input.clear();
+                    self.timer.reset();
+                    self.inspection_start = Some(at);
+                } else {
+                    self.message = None;
+                    self.input.clear();
+                    self.end_inspection(at);
+                    self.timer.reset();
+                    self.timer.start_at(at);
+                }
+            },
+            Some(Action::SolveDetail) if !self.session().solves().is_empty() => {
+                self.open_solve_detail(self.session().solves().len() - 1);
+            },
+            Some(Action::NextPuzzle) if !running => {
+                self.switch_puzzle(self.session().puzzle().next());
+            },
+            Some(Action::SplitMode) if !running => {
+                self.split_mode = !self.split_mode;
+            },
+            Some(Action::Precision) if !running => {
+                self.timer_display.precision = self.timer_display.precision.next();
+            },
+            Some(Action::LargeDigits) if !running => {
+                self.timer_display.large_digits = !self.timer_display.large_digits;
+            },
+            Some(Action::Layout) if !running => {
+                self.layout = self.layout.next();
+            },
+            Some(Action::Report) if !running => {
+                self.message = None;
+                self.open_report();
+            },
+            Some(Action::Help) if !running => {
+                self.view = View::Help;
+            },
+            Some(Action::Command) if !running => {
+                self.command_entry = true;
+                self.input.clear();
+                self.message = None;
+            },
+            Some(Action::Rankings) if !running => {
+                self.load_rank_index();
+                self.view = View::Rankings;
+            },
+            Some(Action::Profile) if !running => {
+                self.load_rank_index();
+                self.view = View::Profile;
+            },
+            Some(Action::Compare) if !running => {
+                let side = |session| Side { session, range: None };
+                let other = (self.current_session + 1) % self.sessions.len();
+                self.message = None;
+                self.view = View::Compare { sides: [side(self.current_session), side(other)], focus: 0, editing: false };
+            },
+            Some(Action::Filter) if !running => {
+                self.filter_entry = true;
+                self.input = self.filter.as_ref().map(|query| query.source().to_string()).unwrap_or_default();
+                self.message = None;
+            },
+            Some(Action::Target) if !running && self.session().puzzle().is_timed() => {
+                self.target_entry = true;
+                self.input.clear();
+                self.message = None;
+            },
+            Some(Action::ManualEntry) if !running && self.session().puzzle().is_timed() => {
+                self.manual_entry = !self.manual_entry;
+                self.input.clear();
+                self.message = None;
+                self.timer.reset();
+            },
+            Some(Action::HardScrambles) if !running => {
+                self.scramble_filter = if self.scramble_filter.is_active() {
+                    ScrambleFilter::default()
+                } else {
+                    ScrambleFilter::hard()
+                };
+                self.next_scramble();
+            },
+            _ => {}
+        }
+
+        Ok(())
     }
 
-    pub fn on_key_pressed(&mut self, code: KeyCode) -> Result<()> {
+    fn on_filter_key(&mut self, code: KeyCode) {
         match code {
-            KeyCode::Char(' ') => {
-                match self.timer.state() {
-                    TimerState::Running { .. } => {
-                        self.timer.pause();
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Backspace => {
+                self.input.pop();
+            },
+            KeyCode::Esc => {
+                self.filter_entry = false;
+                self.input.clear();
+                self.message = None;
+            },
+            KeyCode::Enter => match Query::parse(&self.input) {
+                Ok(query) => {
+                    self.set_filter(if query.source().is_empty() { None } else { Some(query) });
+                    self.filter_entry = false;
+                    self.input.clear();
+                    self.message = None;
+                },
+                Err(error) => self.message = Some(error.to_string()),
+            },
+            _ => {}
+        }
+    }
 
-                        let current_scramble = self.current_scramble.take().unwrap();
-                        let solve = Solve::build(current_scramble, Some(self.timer.elapsed()), None)?;
+    // Tab completes the word being typed.
+    fn on_command_key(&mut self, code: KeyCode) -> Result<()> {
+        match code {
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Backspace => {
+                self.input.pop();
+            },
+            KeyCode::Tab => self.input = command::complete(&self.input),
+            KeyCode::Esc => {
+                self.command_entry = false;
+                self.input.clear();
+                self.message = None;
+            },
+            KeyCode::Enter => match Command::parse(&self.input) {
+                Ok(command) => {
+                    self.command_entry = false;
+                    self.input.clear();
+                    self.message = None;
+                    self.run_command(command)?;
+                },
+                Err(error) => self.message = Some(error.to_string()),
+            },
+            _ => {}
+        }
 
-                        self.session.save_solve(solve);
-                        self.storage.save_session(&self.session)?;
+        Ok(())
+    }
 
-                        self.current_scramble = Some(Scrambler::new_scramble(Puzzle::Cube3x3));
-                    }
-                    _ => {
-                        self.timer.reset();
-                        self.timer.start();
-                    }
+    pub fn run_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::NewSession(puzzle) => {
+                self.sessions.push(Session::new(puzzle));
+                self.switch_puzzle(puzzle);
+                self.storage.save_sessions(&self.sessions)?;
+                self.message = Some(format!("Started a new {} session", puzzle.name()));
+            },
+            Command::Puzzle(puzzle) => self.switch_puzzle(puzzle),
+            // The path comes from the user, so a bad one is only reported.
+            Command::Export(format, path) => {
+                self.message = Some(match self.storage.export_to(Path::new(&path), &self.export(format)) {
+                    Ok(path) => format!("Exported {}", path.display()),
+                    Err(error) => error.to_string(),
+                });
+            },
+            Command::Penalty(penalty) => {
+                let Some(index) = self.session().solves().len().checked_sub(1) else {
+                    self.message = Some("No solve to change".to_string());
+                    return Ok(());
+                };
+                if !self.session().puzzle().is_timed() {
+                    self.message = Some(format!("{} results have no penalties", self.session().puzzle().name()));
+                    return Ok(());
                 }
+
+                let mut changed = Ok(());
+                self.sessions[self.current_session].edit_solve(index, |solve| changed = solve.set_penalty(penalty));
+                self.refresh_filtered_stats();
+                self.message = Some(match changed {
+                    Ok(()) => {
+                        self.storage.save_sessions(&self.sessions)?;
+                        format!("Last solve: {}", ui::format_solve_result(&self.session().solves()[index]))
+                    },
+                    Err(_) => "A DNF has no time to go back to".to_string(),
+                });
             },
-            KeyCode::Char('q') => {
-                self.storage.save_session(&self.session)?;
+            Command::Help => self.view = View::Help,
+            Command::Quit => {
+                self.storage.save_sessions(&self.sessions)?;
                 self.should_quit = true;
             },
+        }
+
+        Ok(())
+    }
+
+    // The solves shown, as written by an export.
+    fn export(&self, format: ExportFormat) -> String {
+        let puzzle = self.shown_puzzle();
+        match format {
+            ExportFormat::Csv => report::to_csv(self.filtered_solves()),
+            ExportFormat::Markdown => report::to_markdown(puzzle, &report::daily_summaries(self.filtered_solves(), &Local)),
+            ExportFormat::Text => report::to_text(puzzle, &report::daily_summaries(self.filtered_solves(), &Local)),
+        }
+    }
+
+    fn on_target_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char(c) if c.is_ascii_digit() || c == ':' || c == '.' => self.input.push(c),
+            KeyCode::Backspace => {
+                self.input.pop();
+            },
+            KeyCode::Esc => {
+                self.target_entry = false;
+                self.input.clear();
+            },
+            KeyCode::Enter => {
+                let target = if self.input.trim().is_empty() { Ok(None) } else { ui::parse_time(&self.input).map(Some) };
+                match target {
+                    Ok(target) => {
+                        self.sessions[self.current_session].set_target(target);
+                        self.target_entry = false;
+                        self.input.clear();
+                        self.message = None;
+                    },
+                    Err(error) => self.message = Some(error.to_string()),
+                }
+            },
+            _ => {}
+        }
+    }
+
+    // In manual entry mode results timed elsewhere are typed in instead of
+    // using the timer. Returns whether the key was handled here.
+    fn on_manual_key(&mut self, code: KeyCode) -> Result<bool> {
+        match code {
+            KeyCode::Char(c) if c.is_ascii_digit() || ":.+dnfDNF".contains(c) => self.input.push(c),
+            KeyCode::Char(' ') => {},
+            KeyCode::Backspace => {
+                self.input.pop();
+            },
+            KeyCode::Esc => {
+                self.manual_entry = false;
+                self.input.clear();
+                self.message = None;
+            },
+            KeyCode::Enter => match ui::parse_solve_result(&self.input) {
+                Ok((time, penalty)) => {
+                    self.input.clear();
+                    self.message = None;
+
+                    let solve = Solve::build(self.current_scramble.take().unwrap(), time, penalty)?;
+                    self.record_solve(solve)?;
+                },
+                Err(error) => self.message = Some(error.to_string()),
+            },
+            _ => return Ok(false)
+        }
+
+        Ok(true)
+    }
+
+    // Multi-blind attempts go through declaring the cube count, timing, and
+    // entering the result. Returns whether the key was handled here, which
+    // leaves other keys, such as the filter's `/` while declaring, to the
+    // timer.
+    fn on_multi_bld_key(&mut self, code: KeyCode, running: bool, at: Instant) -> Result<bool> {
+        use MultiBldStage::*;
+
+        let start_stop = self.keymap.action(code) == Some(Action::StartStop);
+        match (self.multi_bld_stage, code) {
+            (Ready { .. }, _) if start_stop && !running => {
+                self.message = None;
+                self.timer.reset();
+                self.timer.start_at(at);
+            },
+            (Ready { cubes }, _) if start_stop => {
+                self.timer.pause_at(at);
+                let time = self.timer.elapsed().min(mbld::time_limit(cubes));
+                self.multi_bld_stage = Entering { cubes, time };
+            },
+            (Declaring, KeyCode::Char(c)) if c.is_ascii_digit() => self.input.push(c),
+            (Entering { .. }, KeyCode::Char(c)) if c.is_ascii_digit() || c == '/' => self.input.push(c),
+            (Declaring | Entering { .. }, _) if start_stop => {},
+            (Declaring | Entering { .. }, KeyCode::Backspace) => {
+                self.input.pop();
+            },
+            (Declaring, KeyCode::Enter) => match mbld::parse_cube_count(&self.input) {
+                Ok(cubes) => {
+                    self.current_scramble = Some(Scrambler::new_multi_bld_scramble(cubes));
+                    self.multi_bld_stage = Ready { cubes };
+                    self.input.clear();
+                    self.message = None;
+                },
+                Err(error) => self.message = Some(error.to_string()),
+            },
+            (Entering { cubes, time }, KeyCode::Enter) => match mbld::parse_result(&self.input, cubes) {
+                Ok(result) => {
+                    self.input.clear();
+                    self.message = None;
+
+                    let solve = Solve::build_multi_bld(self.current_scramble.take().unwrap(), time, result);
+                    self.record_solve(solve)?;
+                },
+                Err(error) => self.message = Some(error.to_string()),
+            },
+            _ => return Ok(false)
+        }
+
+        Ok(true)
+    }
+
+    // While a fewest moves attempt is running, keys edit the solution instead.
+    fn on_solution_key(&mut self, code: KeyCode) -> Result<()> {
+        match code {
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Backspace => {
+                self.input.pop();
+            },
+            KeyCode::Enter => self.submit_solution()?,
             _ => {}
         }
 
         Ok(())
     }
+
+    // An empty solution is a DNF. Once time is up whatever has been typed is
+    // handed in, so a wrong solution is a DNF rather than one to fix.
+    fn submit_solution(&mut self) -> Result<()> {
+        let solution = self.input.trim().to_string();
+        let scramble = self.current_scramble.as_deref().unwrap();
+        let time_up = self.timer.elapsed() >= fmc::TIME_LIMIT;
+
+        let moves = if solution.is_empty() {
+            None
+        } else {
+            match fmc::verify(scramble, &solution) {
+                Ok(moves) => Some(moves),
+                Err(_) if time_up => None,
+                Err(error) => {
+                    self.message = Some(error.to_string());
+                    return Ok(());
+                }
+            }
+        };
+
+        self.timer.pause();
+        self.message = None;
+        self.input.clear();
+
+        let solve = Solve::build_fmc(self.current_scramble.take().unwrap(), solution, moves);
+        self.record_solve(solve)
+    }
+
+    // How long inspection took sets the penalty of the solve it starts.
+    fn end_inspection(&mut self, at: Instant) {
+        self.inspection_penalty = self.inspection_start.take()
+            .and_then(|start| timer::inspection_penalty(at.saturating_duration_since(start)));
+    }
+
+    pub fn inspection_elapsed(&self) -> Option<Duration> {
+        Some(self.timer.now().saturating_duration_since(self.inspection_start?))
+    }
+
+    // A DNF from inspection still keeps the time, which counts as practice.
+    fn build_timed_solve(&mut self, time: Duration) -> Result<Solve> {
+        let mut solve = Solve::build(self.current_scramble.take().unwrap(), Some(time), None)?;
+        solve.set_penalty(self.inspection_penalty.take())?;
+        Ok(solve)
+    }
+
+    fn record_solve(&mut self, mut solve: Solve) -> Result<()> {
+        solve.set_cross_length(self.current_cross_length);
+        solve.set_date(self.timer.date());
+        self.sessions[self.current_session].save_solve(solve);
+        self.push_filtered_stats();
+        self.storage.save_sessions(&self.sessions)?;
+
+        self.next_scramble();
+        Ok(())
+    }
+
+    fn next_scramble(&mut self) {
+        if self.session().puzzle() == Puzzle::MultiBld {
+            self.current_scramble = None;
+            self.current_cross_length = None;
+            self.multi_bld_stage = MultiBldStage::Declaring;
+            return;
+        }
+
+        let puzzle = self.session().puzzle();
+        let (scramble, cross_length) = match Scrambler::new_filtered_scramble(puzzle, &self.scramble_filter, &mut rand::thread_rng()) {
+            Ok(scramble) => scramble,
+            Err(error) => {
+                self.message = Some(format!("{}, showing an unfiltered one", error));
+                let scramble = Scrambler::new_scramble(puzzle);
+                let cross_length = self.scramble_filter.cross_length(puzzle, &scramble);
+                (scramble, cross_length)
+            },
+        };
+        self.current_scramble = Some(scramble);
+        self.current_cross_length = cross_length;
+    }
+
+    fn on_detail_key(&mut self, code: KeyCode, index: usize) {
+        match (code, self.keymap.action(code)) {
+            (KeyCode::Left, _) if index > 0 => self.open_solve_detail(index - 1),
+            (KeyCode::Right, _) if index + 1 < self.session().solves().len() => self.open_solve_detail(index + 1),
+            (_, Some(Action::Tags)) => {
+                self.input = self.session().solves()[index].tags().join(" ");
+                if let View::SolveDetail { tagging, .. } = &mut self.view {
+                    *tagging = true;
+                }
+            },
+            (KeyCode::Esc, _) | (_, Some(Action::SolveDetail)) => self.view = View::Timer,
+            _ => {}
+        }
+    }
+
+    // Tags are typed separated by spaces or commas.
+    fn on_tag_key(&mut self, code: KeyCode, index: usize) -> Result<()> {
+        match code {
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Backspace => {
+                self.input.pop();
+            },
+            KeyCode::Enter => {
+                let tags = self.input.split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_lowercase)
+                    .collect();
+                self.sessions[self.current_session].edit_solve(index, |solve| solve.set_tags(tags));
+                self.refresh_filtered_stats();
+                self.storage.save_sessions(&self.sessions)?;
+                self.input.clear();
+                self.stop_tagging();
+            },
+            KeyCode::Esc => {
+                self.input.clear();
+                self.stop_tagging();
+            },
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn on_report_key(&mut self, code: KeyCode) -> Result<()> {
+        match (code, self.keymap.action(code)) {
+            (_, Some(Action::Export)) => {
+                let name = format!("report-{}", self.shown_puzzle().name().replace(' ', "-"));
+                let exported = self.storage.export(&format!("{}.md", name), &self.export(ExportFormat::Markdown))
+                    .and_then(|markdown| Ok((markdown, self.storage.export(&format!("{}.txt", name), &self.export(ExportFormat::Text))?)));
+                self.message = Some(match exported {
+                    Ok((markdown, text)) => format!("Exported {} and {}", markdown.display(), text.display()),
+                    Err(error) => error.to_string(),
+                });
+            },
+            (KeyCode::Esc, _) | (_, Some(Action::Report)) => {
+                self.message = None;
+                self.view = View::Timer;
+            },
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    // Left and Right pick the focused side's session, and the date range key
+    // edits its dates.
+    fn on_compare_key(&mut self, code: KeyCode) {
+        let View::Compare { sides, focus, editing } = &mut self.view else { return };
+        let side = &mut sides[*focus];
+        let sessions = self.sessions.len();
+
+        if *editing {
+            match code {
+                KeyCode::Char(c) if c.is_ascii_digit() || c == '-' || c == '.' => self.input.push(c),
+                KeyCode::Backspace => {
+                    self.input.pop();
+                },
+                KeyCode::Enter => match compare::parse_range(&self.input) {
+                    Ok(range) => {
+                        side.range = range;
+                        *editing = false;
+                        self.input.clear();
+                        self.message = None;
+                    },
+                    Err(error) => self.message = Some(error.to_string()),
+                },
+                KeyCode::Esc => {
+                    *editing = false;
+                    self.input.clear();
+                    self.message = None;
+                },
+                _ => {}
+            }
+            return;
+        }
+
+        match (code, self.keymap.action(code)) {
+            (KeyCode::Tab, _) => *focus = 1 - *focus,
+            (KeyCode::Right, _) => side.session = (side.session + 1) % sessions,
+            (KeyCode::Left, _) => side.session = (side.session + sessions - 1) % sessions,
+            (_, Some(Action::DateRange)) => {
+                *editing = true;
+                self.input.clear();
+            },
+            (KeyCode::Esc, _) | (_, Some(Action::Compare)) => self.view = View::Timer,
+            _ => {}
+        }
+    }
+
+    // Each puzzle gets its own session, created the first time it is selected.
+    // When a puzzle has several, the newest is used.
+    fn switch_puzzle(&mut self, puzzle: Puzzle) {
+        self.current_session = match self.sessions.iter().rposition(|s| s.puzzle() == puzzle) {
+            Some(index) => index,
+            None => {
+                self.sessions.push(Session::new(puzzle));
+                self.sessions.len() - 1
+            }
+        };
+        self.refresh_filtered_stats();
+
+        if !puzzle.is_timed() {
+            self.manual_entry = false;
+        }
+        self.input.clear();
+        self.timer.reset();
+        self.next_scramble();
+    }
+
+    fn open_report(&mut self) {
+        let summaries = report::daily_summaries(self.filtered_solves(), &Local);
+        let forecast = report::forecast(&summaries, self.shown_target());
+        self.view = View::Report { summaries, forecast };
+    }
+
+    // Keeps the analysis already worked out, or still being worked out.
+    fn stop_tagging(&mut self) {
+        if let View::SolveDetail { tagging, .. } = &mut self.view {
+            *tagging = false;
+        }
+    }
+
+    // A scramble that cannot be analysed drops the sender, which leaves the
+    // analysis unavailable.
+    fn open_solve_detail(&mut self, index: usize) {
+        let analysis = if self.session().puzzle().size() == Some(3) {
+            let scramble = self.session().solves()[index].scramble().to_string();
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || {
+                if let Ok(analysis) = solver::analyse(&scramble) {
+                    let _ = sender.send(analysis);
+                }
+            });
+            Analysis::Pending(receiver)
+        } else {
+            Analysis::Unavailable
+        };
+        self.view = View::SolveDetail { index, analysis, tagging: false };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stackmat::Packet;
+    use crate::storage::MemoryStorage;
+    use crate::timer::FakeClock;
+    use crate::wca::WcaError;
+    use ratatui::{backend::TestBackend, Terminal};
+    use std::path::PathBuf;
+
+    fn scripted_app() -> (App, FakeClock) {
+        configured_app(Config::default())
+    }
+
+    fn configured_app(config: Config) -> (App, FakeClock) {
+        let clock = FakeClock::new();
+        let app = App::build_with_clock(Box::new(MemoryStorage::default()), config, Box::new(clock.clone())).unwrap();
+        (app, clock)
+    }
+
+    fn press(app: &mut App, clock: &FakeClock, code: KeyCode) {
+        app.on_key_pressed(code, clock.now()).unwrap();
+    }
+
+    #[test]
+    fn space_times_a_solve() {
+        let (mut app, clock) = scripted_app();
+        let scramble = app.current_scramble.clone().unwrap();
+
+        press(&mut app, &clock, KeyCode::Char(' '));
+        clock.advance(Duration::from_millis(12340));
+        assert_eq!(app.timer.elapsed(), Duration::from_millis(12340));
+        press(&mut app, &clock, KeyCode::Char(' '));
+
+        let solve = app.session().solves().last().unwrap();
+        assert_eq!(solve.result(), Some(Duration::from_millis(12340)));
+        assert_eq!(solve.scramble(), scramble);
+        assert!(solve.cross_length().is_some());
+        assert_ne!(app.current_scramble, Some(scramble));
+    }
+
+    #[test]
+    fn solves_are_dated_by_the_clock() {
+        let (mut app, clock) = scripted_app();
+        for _ in 0..2 {
+            press(&mut app, &clock, KeyCode::Char(' '));
+            clock.advance(Duration::from_secs(10));
+            press(&mut app, &clock, KeyCode::Char(' '));
+            clock.advance(Duration::from_secs(24 * 3600));
+        }
+
+        let dates: Vec<_> = app.session().solves().iter().map(|solve| solve.date().unwrap().to_rfc3339()).collect();
+        assert_eq!(dates, ["2026-01-01T12:00:10+00:00", "2026-01-02T12:00:20+00:00"]);
+        press(&mut app, &clock, KeyCode::Char('r'));
+        assert!(matches!(&app.view, View::Report { summaries, .. } if summaries.len() == 2));
+    }
+
+    #[test]
+    fn split_mode_records_phases() {
+        let (mut app, clock) = scripted_app();
+        press(&mut app, &clock, KeyCode::Char('s'));
+
+        for millis in [1500, 6000, 2500, 2000] {
+            if millis == 1500 {
+                press(&mut app, &clock, KeyCode::Char(' '));
+            }
+            clock.advance(Duration::from_millis(millis));
+            press(&mut app, &clock, KeyCode::Char(' '));
+        }
+
+        let solve = app.session().solves().last().unwrap();
+        assert_eq!(solve.result(), Some(Duration::from_secs(12)));
+        assert_eq!(solve.phases(), [1500, 6000, 2500, 2000].map(Duration::from_millis));
+    }
+
+    #[test]
+    fn manual_entry_records_typed_result() {
+        let (mut app, clock) = scripted_app();
+        press(&mut app, &clock, KeyCode::Char('m'));
+        for c in "12.34+".chars() {
+            press(&mut app, &clock, KeyCode::Char(c));
+        }
+        press(&mut app, &clock, KeyCode::Enter);
+
+        let solve = app.session().solves().last().unwrap();
+        assert!(matches!(solve.penalty(), Some(Penalty::Plus2)));
+        assert_eq!(solve.result(), Some(Duration::from_millis(12340)));
+
+        for c in "1:".chars() {
+            press(&mut app, &clock, KeyCode::Char(c));
+        }
+        press(&mut app, &clock, KeyCode::Enter);
+        assert!(app.message.is_some());
+        assert_eq!(app.session().solves().len(), 1);
+    }
+
+    #[test]
+    fn target_is_set_and_cleared() {
+        let (mut app, clock) = scripted_app();
+        for code in [KeyCode::Char('t'), KeyCode::Char('1'), KeyCode::Char('5'), KeyCode::Enter] {
+            press(&mut app, &clock, code);
+        }
+        assert_eq!(app.session().target(), Some(Duration::from_secs(15)));
+        assert!(!app.target_entry);
+
+        press(&mut app, &clock, KeyCode::Char('t'));
+        press(&mut app, &clock, KeyCode::Enter);
+        assert_eq!(app.session().target(), None);
+    }
+
+    #[test]
+    fn filter_and_tags() {
+        let (mut app, clock) = scripted_app();
+        for millis in [12000, 25000] {
+            press(&mut app, &clock, KeyCode::Char(' '));
+            clock.advance(Duration::from_millis(millis));
+            press(&mut app, &clock, KeyCode::Char(' '));
+        }
+
+        for code in [KeyCode::Char('d'), KeyCode::Char('g'), KeyCode::Char('o'), KeyCode::Char('q'), KeyCode::Enter] {
+            press(&mut app, &clock, code);
+        }
+        assert!(!app.should_quit);
+        assert_eq!(app.session().solves()[1].tags(), ["oq"]);
+        press(&mut app, &clock, KeyCode::Esc);
+
+        press(&mut app, &clock, KeyCode::Char('/'));
+        for c in "time<20".chars() {
+            press(&mut app, &clock, KeyCode::Char(c));
+        }
+        press(&mut app, &clock, KeyCode::Enter);
+        assert_eq!(app.filtered_solves().count(), 1);
+        assert_eq!(app.stats().count(), 1);
+
+        // The filtered stats follow the solves they cover.
+        press(&mut app, &clock, KeyCode::Char(' '));
+        clock.advance(Duration::from_secs(9));
+        press(&mut app, &clock, KeyCode::Char(' '));
+        assert_eq!(app.stats().count(), 2);
+        assert_eq!(app.stats().best(), Some(Duration::from_secs(9)));
+        press(&mut app, &clock, KeyCode::Char(' '));
+        clock.advance(Duration::from_secs(30));
+        press(&mut app, &clock, KeyCode::Char(' '));
+        let rebuilt = SessionStats::from_solves(app.filtered_solves());
+        assert_eq!((app.stats().count(), app.stats().avg()), (rebuilt.count(), rebuilt.avg()));
+        assert_eq!(app.stats().count(), 2);
+
+        press(&mut app, &clock, KeyCode::Char('/'));
+        for _ in 0..7 {
+            press(&mut app, &clock, KeyCode::Backspace);
+        }
+        for c in "tag:oq puzzle:3x3".chars() {
+            press(&mut app, &clock, KeyCode::Char(c));
+        }
+        press(&mut app, &clock, KeyCode::Enter);
+        let solves: Vec<_> = app.filtered_solves().collect();
+        assert_eq!(solves.len(), 1);
+        assert_eq!(solves[0].result(), Some(Duration::from_secs(25)));
+        assert_eq!(app.stats().best(), Some(Duration::from_secs(25)));
+        app.set_filter(None);
+        assert_eq!(app.stats().count(), 4);
+    }
+
+    // The detail opens before its analysis is worked out, keeps it while
+    // tagging, and shows it once a tick picks it up.
+    #[test]
+    fn cross_analysis_arrives_on_tick() {
+        let (mut app, clock) = scripted_app();
+        press(&mut app, &clock, KeyCode::Char(' '));
+        clock.advance(Duration::from_secs(10));
+        press(&mut app, &clock, KeyCode::Char(' '));
+
+        press(&mut app, &clock, KeyCode::Char('d'));
+        assert!(matches!(app.view, View::SolveDetail { analysis: Analysis::Pending(_), .. }));
+        press(&mut app, &clock, KeyCode::Char('g'));
+        press(&mut app, &clock, KeyCode::Esc);
+        for _ in 0..600 {
+            app.on_tick().unwrap();
+            if !matches!(app.view, View::SolveDetail { analysis: Analysis::Pending(_), .. }) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(matches!(app.view, View::SolveDetail { analysis: Analysis::Done(_), tagging: false, .. }));
+    }
+
+    #[test]
+    fn filter_naming_a_puzzle_shows_it() {
+        let (mut app, clock) = scripted_app();
+        app.sessions[0].set_target(Some(Duration::from_secs(15)));
+        app.switch_puzzle(Puzzle::Cube3x3Bld);
+        app.switch_puzzle(Puzzle::Cube3x3);
+        assert_eq!((app.shown_puzzle(), app.shown_target()), (Puzzle::Cube3x3, Some(Duration::from_secs(15))));
+
+        app.set_filter(Some(Query::parse("puzzle:3bld").unwrap()));
+        assert_eq!((app.shown_puzzle(), app.shown_target()), (Puzzle::Cube3x3Bld, None));
+        press(&mut app, &clock, KeyCode::Char('r'));
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|frame| ui::draw(frame, &app)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("3BLD by day (puzzle:3bld)"));
+    }
+
+    #[test]
+    fn configured_keys_and_puzzle() {
+        let config = Config::parse("default_puzzle = \"4x4\"\n[keys]\nstart_stop = \"x\"\nquit = \"e\"\nrankings = \"k\"\nexport = \"n\"\nreport = \"b\"").unwrap();
+        let (mut app, clock) = configured_app(config);
+        assert_eq!(app.session().puzzle(), Puzzle::Cube4x4);
+
+        press(&mut app, &clock, KeyCode::Char(' '));
+        assert!(matches!(app.timer.state(), TimerState::Idle));
+        press(&mut app, &clock, KeyCode::Char('x'));
+        clock.advance(Duration::from_secs(40));
+        press(&mut app, &clock, KeyCode::Char('x'));
+        assert_eq!(app.session().solves()[0].result(), Some(Duration::from_secs(40)));
+
+        press(&mut app, &clock, KeyCode::Char('q'));
+        assert!(!app.should_quit);
+
+        // Views close with their own key, and quitting only works from the
+        // timer screen outside of typing.
+        press(&mut app, &clock, KeyCode::Char('k'));
+        press(&mut app, &clock, KeyCode::Char('e'));
+        assert!(matches!(app.view, View::Rankings) && !app.should_quit);
+        press(&mut app, &clock, KeyCode::Char('k'));
+        assert!(matches!(app.view, View::Timer));
+        press(&mut app, &clock, KeyCode::Char('/'));
+        press(&mut app, &clock, KeyCode::Char('e'));
+        assert_eq!(app.input, "e");
+        assert!(!app.should_quit);
+        press(&mut app, &clock, KeyCode::Esc);
+
+        // Keys within views are rebound like any other.
+        press(&mut app, &clock, KeyCode::Char('b'));
+        press(&mut app, &clock, KeyCode::Char('n'));
+        assert_eq!(app.message.as_deref(), Some("IO error: Nothing is written to disk with memory storage"));
+        press(&mut app, &clock, KeyCode::Char('r'));
+        assert!(matches!(app.view, View::Report { .. }));
+        press(&mut app, &clock, KeyCode::Char('b'));
+        assert!(matches!(app.view, View::Timer));
+
+        press(&mut app, &clock, KeyCode::Char('e'));
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn inspection_penalties() {
+        let (mut app, clock) = configured_app(Config::parse("inspection = true").unwrap());
+        for (inspected, penalty) in [(10, None), (16, Some(Penalty::Plus2)), (18, Some(Penalty::DNF))] {
+            press(&mut app, &clock, KeyCode::Char(' '));
+            assert!(matches!(app.timer.state(), TimerState::Idle));
+            clock.advance(Duration::from_secs(inspected));
+            assert_eq!(app.inspection_elapsed(), Some(Duration::from_secs(inspected)));
+            press(&mut app, &clock, KeyCode::Char(' '));
+            clock.advance(Duration::from_secs(10));
+            press(&mut app, &clock, KeyCode::Char(' '));
+            assert_eq!(app.session().solves().last().unwrap().penalty(), &penalty);
+        }
+        let results: Vec<_> = app.session().solves().iter().map(Solve::result).collect();
+        assert_eq!(results, [Some(Duration::from_secs(10)), Some(Duration::from_secs(12)), None]);
+        assert_eq!(app.session().solves()[2].time(), &Some(Duration::from_secs(10)));
+
+        // Other keys wait until inspection is over, and escape cancels it.
+        press(&mut app, &clock, KeyCode::Char(' '));
+        press(&mut app, &clock, KeyCode::Char('r'));
+        assert!(matches!(app.view, View::Timer));
+        press(&mut app, &clock, KeyCode::Esc);
+        assert_eq!(app.inspection_elapsed(), None);
+
+        // Blindfolded solves are timed from the start, inspection included.
+        app.switch_puzzle(Puzzle::Cube3x3Bld);
+        press(&mut app, &clock, KeyCode::Char(' '));
+        assert_eq!(app.inspection_elapsed(), None);
+        assert!(matches!(app.timer.state(), TimerState::Running { .. }));
+    }
+
+    #[test]
+    fn fmc_attempt_ends_when_time_is_up() {
+        let (mut app, clock) = scripted_app();
+        app.switch_puzzle(Puzzle::Cube3x3Fmc);
+        press(&mut app, &clock, KeyCode::Char(' '));
+        for c in "R U".chars() {
+            press(&mut app, &clock, KeyCode::Char(c));
+        }
+
+        clock.advance(fmc::TIME_LIMIT - Duration::from_secs(1));
+        app.on_tick().unwrap();
+        assert!(app.session().solves().is_empty());
+
+        clock.advance(Duration::from_secs(1));
+        app.on_tick().unwrap();
+        assert!(matches!(app.timer.state(), TimerState::Paused { .. }));
+        let solve = &app.session().solves()[0];
+        assert_eq!((solve.solution(), solve.moves()), (Some("R U"), None));
+    }
+
+    #[test]
+    fn entries_work_in_multi_blind() {
+        let (mut app, clock) = scripted_app();
+        app.switch_puzzle(Puzzle::MultiBld);
+        let type_in = |app: &mut App, text: &str| {
+            for c in text.chars() {
+                press(app, &clock, KeyCode::Char(c));
+            }
+            press(app, &clock, KeyCode::Enter);
+        };
+
+        type_in(&mut app, "/puzzle:mbld");
+        assert!(!app.filter_entry);
+        assert_eq!(app.filter.as_ref().map(Query::source), Some("puzzle:mbld"));
+        assert_eq!(app.multi_bld_stage, MultiBldStage::Declaring);
+
+        type_in(&mut app, "2");
+        assert_eq!(app.multi_bld_stage, MultiBldStage::Ready { cubes: 2 });
+        type_in(&mut app, ":session new 3x3");
+        assert_eq!(app.message.as_deref(), Some("Started a new 3x3 session"));
+        app.switch_puzzle(Puzzle::MultiBld);
+        type_in(&mut app, ":quit");
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn multi_blind_attempt_stops_at_the_limit() {
+        let (mut app, clock) = scripted_app();
+        app.switch_puzzle(Puzzle::MultiBld);
+        press(&mut app, &clock, KeyCode::Char('2'));
+        press(&mut app, &clock, KeyCode::Enter);
+        press(&mut app, &clock, KeyCode::Char(' '));
+
+        let limit = mbld::time_limit(2);
+        clock.advance(limit - Duration::from_secs(1));
+        app.on_tick().unwrap();
+        assert_eq!(app.multi_bld_stage, MultiBldStage::Ready { cubes: 2 });
+
+        clock.advance(Duration::from_secs(5));
+        app.on_tick().unwrap();
+        assert!(matches!(app.timer.state(), TimerState::Paused { .. }));
+        assert_eq!(app.multi_bld_stage, MultiBldStage::Entering { cubes: 2, time: limit });
+
+        for c in "2/2".chars() {
+            press(&mut app, &clock, KeyCode::Char(c));
+        }
+        press(&mut app, &clock, KeyCode::Enter);
+        assert_eq!(app.session().solves()[0].result(), Some(limit));
+    }
+
+    #[test]
+    fn rankings_view() {
+        let (mut app, clock) = scripted_app();
+        press(&mut app, &clock, KeyCode::Char('w'));
+        assert!(matches!(app.view, View::Rankings));
+        assert!(app.rank_index.is_none());
+        press(&mut app, &clock, KeyCode::Esc);
+        assert!(matches!(app.view, View::Timer));
+
+        press(&mut app, &clock, KeyCode::Char('a'));
+        assert!(matches!(app.view, View::Profile));
+        assert_eq!(app.reference_records(), Records::default());
+        press(&mut app, &clock, KeyCode::Char('a'));
+        assert!(matches!(app.view, View::Timer));
+    }
+
+    // Memory storage whose rank index cannot be read.
+    struct DamagedRanks(MemoryStorage);
+
+    impl Storage for DamagedRanks {
+        fn save_sessions(&self, sessions: &[Session]) -> Result<()> {
+            self.0.save_sessions(sessions)
+        }
+
+        fn load_sessions(&self) -> Result<Vec<Session>> {
+            self.0.load_sessions()
+        }
+
+        fn export(&self, name: &str, contents: &str) -> Result<PathBuf> {
+            self.0.export(name, contents)
+        }
+
+        fn export_to(&self, path: &Path, contents: &str) -> Result<PathBuf> {
+            self.0.export_to(path, contents)
+        }
+
+        fn save_rank_index(&self, index: &RankIndex) -> Result<()> {
+            self.0.save_rank_index(index)
+        }
+
+        fn load_rank_index(&self) -> Result<Option<RankIndex>> {
+            Err(WcaError::CorruptIndex.into())
+        }
+    }
+
+    #[test]
+    fn damaged_rank_index_is_reported() {
+        let clock = FakeClock::new();
+        let storage = Box::new(DamagedRanks(MemoryStorage::default()));
+        let mut app = App::build_with_clock(storage, Config::default(), Box::new(clock.clone())).unwrap();
+
+        press(&mut app, &clock, KeyCode::Char('w'));
+        assert!(matches!(app.view, View::Rankings));
+        assert!(app.rank_index.is_none());
+        assert_eq!(app.message.as_deref(), Some("WCA export error: The imported rankings are damaged, import the WCA export again"));
+    }
+
+    #[test]
+    fn stackmat_time_is_recorded() {
+        let (mut app, clock) = scripted_app();
+        let packet = |status, millis| Packet { status, time: Duration::from_millis(millis) };
+
+        app.on_stackmat_packet(packet(Status::Ready, 0)).unwrap();
+        app.on_stackmat_packet(packet(Status::Running, 0)).unwrap();
+        clock.advance(Duration::from_secs(9));
+        app.on_stackmat_packet(packet(Status::Running, 9000)).unwrap();
+        app.on_stackmat_packet(packet(Status::Stopped, 9123)).unwrap();
+        app.on_stackmat_packet(packet(Status::Stopped, 9123)).unwrap();
+
+        assert_eq!(app.session().solves().len(), 1);
+        assert_eq!(app.session().solves()[0].result(), Some(Duration::from_millis(9123)));
+    }
+
+    #[test]
+    fn layouts_at_any_size() {
+        let (mut app, clock) = scripted_app();
+        press(&mut app, &clock, KeyCode::Char(' '));
+        clock.advance(Duration::from_millis(12345));
+        press(&mut app, &clock, KeyCode::Char(' '));
+
+        for layout in [LayoutPreset::Auto, LayoutPreset::Wide, LayoutPreset::Stacked, LayoutPreset::Minimal] {
+            assert_eq!(app.layout, layout);
+            for (width, height) in [(1, 1), (20, 6), (60, 30), (160, 50)] {
+                let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+                terminal.draw(|frame| ui::draw(frame, &app)).unwrap();
+            }
+            press(&mut app, &clock, KeyCode::Char('v'));
+        }
+        assert_eq!(app.layout, LayoutPreset::Auto);
+    }
+
+    #[test]
+    fn command_palette() {
+        let (mut app, clock) = scripted_app();
+        let command = |app: &mut App, text: &str| {
+            press(app, &clock, KeyCode::Char(':'));
+            for c in text.chars() {
+                press(app, &clock, if c == '\t' { KeyCode::Tab } else { KeyCode::Char(c) });
+            }
+            press(app, &clock, KeyCode::Enter);
+        };
+
+        press(&mut app, &clock, KeyCode::Char(' '));
+        clock.advance(Duration::from_millis(12340));
+        press(&mut app, &clock, KeyCode::Char(' '));
+        command(&mut app, "pe\td\t");
+        assert_eq!(app.session().solves()[0].result(), None);
+        assert_eq!(app.message.as_deref(), Some("Last solve: DNF"));
+        command(&mut app, "penalty +2");
+        assert_eq!(app.session().solves()[0].result(), Some(Duration::from_millis(14340)));
+        assert_eq!(app.message.as_deref(), Some("Last solve: 14.34+"));
+
+        command(&mut app, "export csv solves.csv");
+        assert_eq!(app.message.as_deref(), Some("IO error: Nothing is written to disk with memory storage"));
+
+        command(&mut app, "se\t\t4x\t");
+        assert_eq!(app.session().puzzle(), Puzzle::Cube4x4);
+        command(&mut app, "session new 3x3");
+        assert_eq!(app.session().puzzle(), Puzzle::Cube3x3);
+        assert!(app.session().solves().is_empty());
+        assert_eq!(app.sessions.len(), 3);
+        command(&mut app, "puzzle 4x4");
+        command(&mut app, "puzzle 3x3");
+        assert_eq!(app.current_session, 2);
+
+        // A bad command stays open to be fixed, and q is typed rather than
+        // quitting.
+        command(&mut app, "sq");
+        assert!(app.command_entry);
+        assert_eq!(app.message.as_deref(), Some("Unknown command: sq"));
+        press(&mut app, &clock, KeyCode::Esc);
+        assert!(!app.command_entry && !app.should_quit);
+
+        press(&mut app, &clock, KeyCode::Char('?'));
+        assert!(matches!(app.view, View::Help));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| ui::draw(frame, &app)).unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(80, 40)).unwrap();
+        terminal.draw(|frame| ui::draw(frame, &app)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        for listed in ["start or stop the timer", "g      edit the shown solve's tags", "export the daily report", "between solved and attempted", "Commands, after :"] {
+            assert!(screen.contains(listed), "{} is not in the help", listed);
+        }
+        press(&mut app, &clock, KeyCode::Char('?'));
+        assert!(matches!(app.view, View::Timer));
+
+        command(&mut app, "quit");
+        assert!(app.should_quit);
+    }
 }
@@ -0,0 +1,188 @@
+use crate::scramble::Puzzle;
+use crate::solve::Penalty;
+
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CommandError {
+    #[error("Unknown command: {0}")]
+    Unknown(String),
+    #[error("Usage: {0}")]
+    Usage(&'static str),
+    #[error("Unknown puzzle: {0}")]
+    InvalidPuzzle(String)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Markdown,
+    Text
+}
+
+// What the `:` command palette can do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    NewSession(Puzzle),
+    Puzzle(Puzzle),
+    Export(ExportFormat, String),
+    Penalty(Option<Penalty>),
+    Help,
+    Quit
+}
+
+// Each command's usage and what it does, as listed in the help.
+pub const COMMANDS: [(&str, &str); 6] = [
+    ("session new <puzzle>", "start a new session"),
+    ("puzzle <puzzle>", "switch to the puzzle's latest session"),
+    ("export csv|markdown|text <path>", "export the shown solves"),
+    ("penalty none|+2|dnf", "change the last solve's penalty"),
+    ("help", "show keys and commands"),
+    ("quit", "save and quit"),
+];
+
+const EXPORT_FORMATS: [(&str, ExportFormat); 3] = [("csv", ExportFormat::Csv), ("markdown", ExportFormat::Markdown), ("text", ExportFormat::Text)];
+const PENALTIES: [(&str, Option<Penalty>); 3] = [("none", None), ("+2", Some(Penalty::Plus2)), ("dnf", Some(Penalty::DNF))];
+
+fn usage(name: &str) -> &'static str {
+    COMMANDS.iter().find(|(usage, _)| usage.split(' ').next() == Some(name)).map(|(usage, _)| *usage).unwrap_or("")
+}
+
+fn puzzle_name(puzzle: Puzzle) -> String {
+    puzzle.name().replace(' ', "-")
+}
+
+fn parse_puzzle(name: Option<&str>, command: &str) -> Result<Puzzle, CommandError> {
+    let name = name.ok_or(CommandError::Usage(usage(command)))?;
+    Puzzle::from_name(name).ok_or_else(|| CommandError::InvalidPuzzle(name.to_string()))
+}
+
+impl Command {
+    pub fn parse(input: &str) -> Result<Command, CommandError> {
+        let mut words = input.split_whitespace();
+        let Some(name) = words.next() else {
+            return Err(CommandError::Usage("<command>, see help"));
+        };
+        let command = match name {
+            "session" => match words.next() {
+                Some("new") => Command::NewSession(parse_puzzle(words.next(), name)?),
+                _ => return Err(CommandError::Usage(usage(name))),
+            },
+            "puzzle" => Command::Puzzle(parse_puzzle(words.next(), name)?),
+            "export" => {
+                let format = words.next()
+                    .and_then(|format| EXPORT_FORMATS.iter().find(|(n, _)| *n == format))
+                    .ok_or(CommandError::Usage(usage(name)))?
+                    .1;
+                // Paths may contain spaces, so the rest of the line is the path.
+                let path = words.collect::<Vec<_>>().join(" ");
+                if path.is_empty() {
+                    return Err(CommandError::Usage(usage(name)));
+                }
+                return Ok(Command::Export(format, path));
+            },
+            "penalty" => {
+                let penalty = words.next()
+                    .and_then(|penalty| PENALTIES.iter().find(|(n, _)| n.eq_ignore_ascii_case(penalty)))
+                    .ok_or(CommandError::Usage(usage(name)))?;
+                Command::Penalty(penalty.1.clone())
+            },
+            "help" => Command::Help,
+            "quit" => Command::Quit,
+            _ => return Err(CommandError::Unknown(name.to_string())),
+        };
+
+        match words.next() {
+            Some(_) => Err(CommandError::Usage(usage(name))),
+            None => Ok(command),
+        }
+    }
+}
+
+// The words that can follow the input's complete words and start with its
+// last, partly typed word.
+pub fn completions(input: &str) -> Vec<String> {
+    let mut words: Vec<_> = input.split_whitespace().collect();
+    let partial = if input.ends_with(char::is_whitespace) || input.is_empty() { "" } else { words.pop().unwrap_or("") };
+
+    let puzzles = || Puzzle::ALL.into_iter().map(puzzle_name).collect();
+    let candidates: Vec<String> = match words.as_slice() {
+        [] => COMMANDS.iter().filter_map(|(usage, _)| usage.split(' ').next()).map(String::from).collect(),
+        ["session"] => vec!["new".to_string()],
+        ["session", "new"] | ["puzzle"] => puzzles(),
+        ["export"] => EXPORT_FORMATS.iter().map(|(name, _)| name.to_string()).collect(),
+        ["penalty"] => PENALTIES.iter().map(|(name, _)| name.to_string()).collect(),
+        _ => Vec::new(),
+    };
+    candidates.into_iter()
+        .filter(|candidate| candidate.to_lowercase().starts_with(&partial.to_lowercase()))
+        .collect()
+}
+
+// Extends the last word as far as its completions agree, finishing it when
+// there is only one.
+pub fn complete(input: &str) -> String {
+    let candidates = completions(input);
+    let Some(first) = candidates.first() else {
+        return input.to_string();
+    };
+
+    let start = if input.ends_with(char::is_whitespace) { input.len() } else { input.rfind(char::is_whitespace).map_or(0, |i| i + 1) };
+    let common = candidates.iter().fold(first.as_str(), |common, candidate| {
+        let length = common.chars().zip(candidate.chars()).take_while(|(a, b)| a.eq_ignore_ascii_case(b)).count();
+        &common[..common.char_indices().nth(length).map_or(common.len(), |(i, _)| i)]
+    });
+    let mut completed = format!("{}{}", &input[..start], common);
+    if candidates.len() == 1 {
+        completed.push(' ');
+    }
+    completed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(Command::parse("session new 4x4"), Ok(Command::NewSession(Puzzle::Cube4x4)));
+        assert_eq!(Command::parse("  puzzle 2-4-relay "), Ok(Command::Puzzle(Puzzle::Relay2To4)));
+        assert_eq!(Command::parse("export csv my solves.csv"), Ok(Command::Export(ExportFormat::Csv, "my solves.csv".to_string())));
+        assert_eq!(Command::parse("penalty DNF"), Ok(Command::Penalty(Some(Penalty::DNF))));
+        assert_eq!(Command::parse("penalty none"), Ok(Command::Penalty(None)));
+        assert_eq!(Command::parse("help"), Ok(Command::Help));
+        assert_eq!(Command::parse("quit"), Ok(Command::Quit));
+    }
+
+    #[test]
+    fn parse_invalid_commands() {
+        let error = |input| Command::parse(input).unwrap_err().to_string();
+        assert_eq!(error("teleport"), "Unknown command: teleport");
+        assert_eq!(error("session 4x4"), "Usage: session new <puzzle>");
+        assert_eq!(error("session new 8x8"), "Unknown puzzle: 8x8");
+        assert_eq!(error("export csv"), "Usage: export csv|markdown|text <path>");
+        assert_eq!(error("export pdf out.pdf"), "Usage: export csv|markdown|text <path>");
+        assert_eq!(error("penalty +3"), "Usage: penalty none|+2|dnf");
+        assert_eq!(error("quit now"), "Usage: quit");
+        assert_eq!(error(""), "Usage: <command>, see help");
+    }
+
+    #[test]
+    fn complete_commands() {
+        assert_eq!(completions(""), ["session", "puzzle", "export", "penalty", "help", "quit"]);
+        assert_eq!(completions("p"), ["puzzle", "penalty"]);
+        assert_eq!(completions("session new 4"), ["4x4", "4BLD"]);
+        assert_eq!(completions("export "), ["csv", "markdown", "text"]);
+        assert_eq!(completions("export csv out"), Vec::<String>::new());
+
+        assert_eq!(complete("se"), "session ");
+        assert_eq!(complete("session "), "session new ");
+        assert_eq!(complete("p"), "p");
+        assert_eq!(complete("pe"), "penalty ");
+        assert_eq!(complete("puzzle 3b"), "puzzle 3BLD ");
+        assert_eq!(complete("puzzle 2-"), "puzzle 2-");
+        assert_eq!(complete("puzzle 2-4"), "puzzle 2-4-relay ");
+        assert_eq!(complete("puzzle 2x"), "puzzle 2x2 ");
+        assert_eq!(complete("teleport"), "teleport");
+    }
+}
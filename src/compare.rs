@@ -0,0 +1,138 @@
+use crate::math;
+use crate::report::best_average;
+use crate::solve::Solve;
+
+use std::time::Duration;
+use chrono::{NaiveDate, TimeZone};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CompareError {
+    #[error("Invalid date range: {0}, expected YYYY-MM-DD..YYYY-MM-DD")]
+    InvalidRange(String)
+}
+
+// One side of a comparison: a session, optionally narrowed to the solves
+// recorded between two days, inclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Side {
+    pub session: usize,
+    pub range: Option<(NaiveDate, NaiveDate)>
+}
+
+// Accepts `from..to`, a single day, or nothing to clear the range.
+pub fn parse_range(input: &str) -> Result<Option<(NaiveDate, NaiveDate)>, CompareError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+
+    let invalid = || CompareError::InvalidRange(input.to_string());
+    let (from, to) = input.split_once("..").unwrap_or((input, input));
+    let from: NaiveDate = from.trim().parse().map_err(|_| invalid())?;
+    let to: NaiveDate = to.trim().parse().map_err(|_| invalid())?;
+    if from > to {
+        return Err(invalid());
+    }
+
+    Ok(Some((from, to)))
+}
+
+// Results of the solves in range, days taken in the given time zone. Undated
+// solves are only included when there is no range, and fewest moves results,
+// having no time, never are.
+pub fn side_results<'a, Tz: TimeZone>(solves: impl IntoIterator<Item = &'a Solve>, range: Option<(NaiveDate, NaiveDate)>, zone: &Tz) -> Vec<Option<Duration>> {
+    solves.into_iter()
+        .filter(|solve| !solve.is_fewest_moves())
+        .filter(|solve| match (range, solve.date()) {
+            (None, _) => true,
+            (Some((from, to)), Some(date)) => (from..=to).contains(&date.with_timezone(zone).date_naive()),
+            (Some(_), None) => false,
+        })
+        .map(|solve| solve.result())
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SideStats {
+    pub count: usize,
+    pub mean: Option<Duration>,
+    pub best: Option<Duration>,
+    pub best_ao5: Option<Duration>,
+    pub best_ao12: Option<Duration>,
+    pub std: Option<f64>
+}
+
+// The mean and standard deviation leave out DNFs, like the stats panel.
+pub fn side_stats(results: &[Option<Duration>]) -> SideStats {
+    let times: Vec<_> = results.iter().flatten().copied().collect();
+    SideStats {
+        count: results.len(),
+        mean: math::avg(&times),
+        best: math::best(results),
+        best_ao5: best_average(results, 5),
+        best_ao12: best_average(results, 12),
+        std: math::sample_std(results)
+    }
+}
+
+// Welch's t-test on the completed solves of both sides.
+pub fn significance(a: &[Option<Duration>], b: &[Option<Duration>]) -> Option<math::WelchTest> {
+    let seconds = |results: &[Option<Duration>]| results.iter().flatten().map(Duration::as_secs_f64).collect::<Vec<_>>();
+    math::welch_t_test(&seconds(a), &seconds(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve::Penalty;
+    use chrono::{DateTime, Utc};
+
+    #[test]
+    fn parse_ranges() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+        assert_eq!(parse_range("2026-01-01..2026-01-31").unwrap(), Some((day(1), day(31))));
+        assert_eq!(parse_range("2026-01-05").unwrap(), Some((day(5), day(5))));
+        assert_eq!(parse_range("  ").unwrap(), None);
+        assert!(parse_range("2026-01-31..2026-01-01").is_err());
+        assert!(parse_range("yesterday").is_err());
+        assert!(parse_range("2026-01-01..").is_err());
+    }
+
+    #[test]
+    fn results_in_range() {
+        let mut solves = Vec::new();
+        for (date, secs) in [(Some("2026-01-01T10:00:00Z"), 20), (Some("2026-01-02T10:00:00Z"), 18), (None, 25)] {
+            let mut solve = Solve::build("R".to_owned(), Some(Duration::from_secs(secs)), None).unwrap();
+            if let Some(date) = date {
+                solve.set_date(date.parse::<DateTime<Utc>>().unwrap());
+            }
+            solves.push(solve);
+        }
+        solves.push(Solve::build_fmc("R".to_owned(), "R'".to_owned(), Some(1)));
+
+        assert_eq!(side_results(&solves, None, &Utc).len(), 3);
+        let day = NaiveDate::from_ymd_opt(2026, 1, 2).unwrap();
+        assert_eq!(side_results(&solves, Some((day, day)), &Utc), vec![Some(Duration::from_secs(18))]);
+    }
+
+    #[test]
+    fn stats_and_significance() {
+        let slow: Vec<_> = [20.1, 19.5, 21.0, 20.4, 19.9, 20.6].iter().map(|&s| Some(Duration::from_secs_f64(s))).collect();
+        let mut fast: Vec<_> = [17.2, 16.8, 17.9, 17.0, 16.5, 17.4].iter().map(|&s| Some(Duration::from_secs_f64(s))).collect();
+        fast.push(None);
+
+        let stats = side_stats(&fast);
+        assert_eq!(stats.count, 7);
+        assert_eq!(stats.best, Some(Duration::from_millis(16500)));
+        assert!(stats.best_ao5.is_some());
+        assert_eq!(stats.best_ao12, None);
+
+        let test = significance(&slow, &fast).unwrap();
+        assert!(test.t > 0.0);
+        assert!(test.p < 0.001);
+
+        let solve = Solve::build("R".to_owned(), None, Some(Penalty::DNF)).unwrap();
+        assert!(significance(&[solve.result()], &fast).is_none());
+    }
+}
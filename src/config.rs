@@ -0,0 +1,359 @@
+use crate::keys::{is_reserved, parse_key, Action, Keymap, KeyName};
+use crate::scramble::Puzzle;
+use crate::theme::{Theme, THEMES};
+use crate::ui::{LayoutPreset, Precision, TimerDisplay};
+use crate::wca::{Kind, Records};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use ratatui::style::Color;
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("{0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Unknown puzzle: {0}")]
+    InvalidPuzzle(String),
+    #[error("Unknown key {1:?} for {0}")]
+    InvalidKey(String, String),
+    #[error("{1} is reserved and cannot be bound to {0}")]
+    ReservedKey(String, String),
+    #[error("{0} is bound to more than one action")]
+    DuplicateKey(String),
+    #[error("Unknown theme {0:?}, expected one of {list} or a theme under [themes]", list = THEMES.join(", "))]
+    InvalidTheme(String),
+    #[error("Invalid colour {1:?} for {0}")]
+    InvalidColour(String, String),
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error)
+}
+
+// Written out on first run so there is a file to edit.
+pub const DEFAULT_CONFIG: &str = r##"# Puzzle shown at startup, by the name shown in the scramble title.
+default_puzzle = "3x3"
+
+# Start the 15 second WCA inspection before each timed solve. Starting after
+# 15 seconds adds 2 seconds, after 17 seconds the solve is a DNF.
+inspection = false
+
+# WCA country id or ISO code to rank in, such as "Germany" or "DE".
+# country = "DE"
+
+# Where sessions are kept: "file", or "memory" to try things out without
+# saving anything.
+storage = "file"
+
+# Colour theme: "dark", "light", "high-contrast", "solarized" or one of the
+# themes below.
+theme = "dark"
+
+[display]
+# Timer precision while running: "centiseconds", "deciseconds", "seconds"
+# or "hidden".
+precision = "centiseconds"
+large_digits = false
+# Panels of the timer screen: "wide", "stacked", "minimal" for the time
+# alone, or "auto" to pick one to suit the terminal's size.
+layout = "auto"
+
+# Keys for the timer screen: a single character, or one of space, tab,
+# enter, esc, backspace, delete, left, right, up, down, home, end, pageup,
+# pagedown and f1 to f12.
+[keys]
+start_stop = "space"
+quit = "q"
+solve_detail = "d"
+next_puzzle = "tab"
+split_mode = "s"
+hard_scrambles = "h"
+precision = "p"
+large_digits = "l"
+layout = "v"
+manual_entry = "m"
+target = "t"
+filter = "/"
+report = "r"
+compare = "c"
+rankings = "w"
+profile = "a"
+help = "?"
+command = ":"
+# In the solve detail, report and compare screens.
+tags = "g"
+export = "e"
+date_range = "f"
+
+# Colours changed from the theme, as colour names or "#rrggbb": background,
+# text, border, the timer's idle, armed, inspecting and running, then
+# personal_best, plus_two and dnf, and results faster than the target and
+# ao12, between them or slower.
+[colours]
+# running = "cyan"
+
+# Themes of your own, starting from a named theme.
+# [themes.mine]
+# base = "solarized"
+# personal_best = "#ffd700"
+
+# Kinch reference results in place of the imported world records, in the
+# WCA's encoding (centiseconds for times).
+[records]
+# "333" = { single = 305, average = 390 }
+"##;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    File,
+    Memory
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DisplayFile {
+    precision: Option<Precision>,
+    large_digits: Option<bool>,
+    layout: Option<LayoutPreset>
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RecordFile {
+    single: Option<u32>,
+    average: Option<u32>
+}
+
+// The file as written, where anything left out keeps its default.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    default_puzzle: Option<String>,
+    inspection: Option<bool>,
+    country: Option<String>,
+    storage: Option<StorageBackend>,
+    theme: Option<String>,
+    display: Option<DisplayFile>,
+    #[serde(default)]
+    keys: BTreeMap<Action, String>,
+    #[serde(default)]
+    colours: BTreeMap<String, String>,
+    // Each theme's colours, along with the named theme it starts from.
+    #[serde(default)]
+    themes: BTreeMap<String, BTreeMap<String, String>>,
+    #[serde(default)]
+    records: BTreeMap<String, RecordFile>
+}
+
+pub struct Config {
+    pub default_puzzle: Puzzle,
+    pub inspection: bool,
+    pub country: Option<String>,
+    pub storage: StorageBackend,
+    pub timer_display: TimerDisplay,
+    pub layout: LayoutPreset,
+    pub keymap: Keymap,
+    pub theme: Theme,
+    pub records: Records
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            default_puzzle: Puzzle::Cube3x3,
+            inspection: false,
+            country: None,
+            storage: StorageBackend::File,
+            timer_display: TimerDisplay::default(),
+            layout: LayoutPreset::Auto,
+            keymap: Keymap::default(),
+            theme: Theme::default(),
+            records: Records::default()
+        }
+    }
+}
+
+impl Config {
+    // Reads the config, first writing the default one if there is none.
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        if !path.exists() {
+            fs::write(path, DEFAULT_CONFIG)?;
+        }
+        Config::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let file: ConfigFile = toml::from_str(text)?;
+        let mut config = Config::default();
+
+        if let Some(name) = file.default_puzzle {
+            config.default_puzzle = Puzzle::from_name(&name).ok_or(ConfigError::InvalidPuzzle(name))?;
+        }
+        config.inspection = file.inspection.unwrap_or(config.inspection);
+        config.country = file.country;
+        config.storage = file.storage.unwrap_or(config.storage);
+        if let Some(display) = file.display {
+            config.timer_display.precision = display.precision.unwrap_or(config.timer_display.precision);
+            config.timer_display.large_digits = display.large_digits.unwrap_or(config.timer_display.large_digits);
+            config.layout = display.layout.unwrap_or(config.layout);
+        }
+
+        let bindings = file.keys.into_iter()
+            .map(|(action, key)| match parse_key(&key) {
+                Some(code) if is_reserved(code) => Err(ConfigError::ReservedKey(format!("{:?}", action), KeyName(code).to_string())),
+                Some(code) => Ok((action, code)),
+                None => Err(ConfigError::InvalidKey(format!("{:?}", action), key)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        config.keymap = config.keymap.with_bindings(bindings)
+            .map_err(|key| ConfigError::DuplicateKey(KeyName(key).to_string()))?;
+
+        // Every user theme is checked, not only the one in use, so a
+        // mistake shows up before switching to it.
+        let mut themes = BTreeMap::new();
+        for (name, colours) in &file.themes {
+            let base = colours.get("base").map(String::as_str).unwrap_or("dark");
+            let theme = Theme::named(base).ok_or_else(|| ConfigError::InvalidTheme(base.to_string()))?;
+            themes.insert(name, with_colours(theme, colours.iter().filter(|(name, _)| *name != "base"))?);
+        }
+        if let Some(name) = file.theme {
+            config.theme = match themes.get(&name) {
+                Some(theme) => *theme,
+                None => Theme::named(&name).ok_or(ConfigError::InvalidTheme(name))?,
+            };
+        }
+        config.theme = with_colours(config.theme, &file.colours)?;
+
+        for (event, record) in file.records {
+            for (kind, result) in [(Kind::Single, record.single), (Kind::Average, record.average)] {
+                if let Some(result) = result {
+                    config.records.set(&event, kind, result);
+                }
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+fn with_colours<'a>(mut theme: Theme, colours: impl IntoIterator<Item = (&'a String, &'a String)>) -> Result<Theme, ConfigError> {
+    for (name, value) in colours {
+        let invalid = || ConfigError::InvalidColour(name.clone(), value.clone());
+        let colour = value.parse::<Color>().map_err(|_| invalid())?;
+        if !theme.set(name, colour) {
+            return Err(invalid());
+        }
+    }
+    Ok(theme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_dir;
+    use ratatui::crossterm::event::KeyCode;
+
+    #[test]
+    fn default_config_matches_defaults() {
+        let config = Config::parse(DEFAULT_CONFIG).unwrap();
+        let default = Config::default();
+        assert_eq!(config.default_puzzle, default.default_puzzle);
+        assert_eq!(config.keymap, default.keymap);
+        assert_eq!(config.theme, default.theme);
+        assert_eq!(config.timer_display.precision, default.timer_display.precision);
+        assert_eq!(config.layout, default.layout);
+        assert!(!config.inspection);
+        assert!(matches!(config.storage, StorageBackend::File));
+        assert_eq!(config.records, Records::default());
+    }
+
+    #[test]
+    fn parse_config() {
+        let config = Config::parse(r##"
+            default_puzzle = "2-4-relay"
+            inspection = true
+            country = "DE"
+            storage = "memory"
+            theme = "light"
+
+            [display]
+            precision = "hidden"
+            layout = "stacked"
+
+            [keys]
+            start_stop = "x"
+            report = "space"
+
+            [colours]
+            faster = "#00ff00"
+
+            [records]
+            "333" = { average = 390 }
+        "##).unwrap();
+
+        assert_eq!(config.default_puzzle, Puzzle::Relay2To4);
+        assert!(config.inspection);
+        assert_eq!(config.country.as_deref(), Some("DE"));
+        assert!(matches!(config.storage, StorageBackend::Memory));
+        assert_eq!(config.timer_display.precision, Precision::Hidden);
+        assert!(!config.timer_display.large_digits);
+        assert_eq!(config.layout, LayoutPreset::Stacked);
+        assert_eq!(config.keymap.action(KeyCode::Char('x')), Some(Action::StartStop));
+        assert_eq!(config.keymap.action(KeyCode::Char(' ')), Some(Action::Report));
+        assert_eq!(config.theme.faster, Color::Rgb(0, 255, 0));
+        assert_eq!(config.theme.running, Theme::named("light").unwrap().running);
+        assert_eq!(config.records.get("333", Kind::Average), Some(390));
+        assert_eq!(config.records.get("333", Kind::Single), None);
+    }
+
+    #[test]
+    fn user_themes() {
+        let config = Config::parse(r##"
+            theme = "mine"
+
+            [colours]
+            border = "white"
+
+            [themes.mine]
+            base = "solarized"
+            running = "#ffffff"
+
+            [themes.unused]
+            base = "light"
+        "##).unwrap();
+        let solarized = Theme::named("solarized").unwrap();
+        assert_eq!(config.theme, Theme { running: Color::Rgb(255, 255, 255), border: Color::White, ..solarized });
+
+        let config = Config::parse("theme = \"plain\"\n[themes.plain]\ndnf = \"blue\"").unwrap();
+        assert_eq!(config.theme, Theme { dnf: Color::Blue, ..Theme::default() });
+    }
+
+    #[test]
+    fn invalid_configs() {
+        let error = |text| Config::parse(text).err().unwrap().to_string();
+        assert_eq!(error(r#"default_puzzle = "8x8""#), "Unknown puzzle: 8x8");
+        assert_eq!(error("[keys]\nquit = \"hyper\""), "Unknown key \"hyper\" for Quit");
+        assert_eq!(error("[keys]\nquit = \"space\""), "space is bound to more than one action");
+        assert_eq!(error("[keys]\nquit = \"Esc\""), "esc is reserved and cannot be bound to Quit");
+        assert_eq!(error("[keys]\nreport = \"5\""), "5 is reserved and cannot be bound to Report");
+        assert_eq!(error("[colours]\nslower = \"reddish\""), "Invalid colour \"reddish\" for slower");
+        assert!(error(r#"theme = "neon""#).starts_with("Unknown theme \"neon\", expected one of dark, light"));
+        assert!(error("theme = \"mine\"\n[themes.mine]\nbase = \"mine\"").starts_with("Unknown theme \"mine\""));
+        assert_eq!(error("[themes.unused]\nrunning = \"nonsense\""), "Invalid colour \"nonsense\" for running");
+        assert!(error("[themes.unused]\nbase = \"neon\"").starts_with("Unknown theme \"neon\""));
+        assert_eq!(error("[colours]\nsparkle = \"red\""), "Invalid colour \"red\" for sparkle");
+        assert!(error("[keys]\nteleport = \"x\"").contains("teleport"));
+        assert!(error("inspection = \"yes\"").contains("inspection"));
+        assert!(error("spelling_mistake = 1").contains("spelling_mistake"));
+    }
+
+    #[test]
+    fn writes_default_config() {
+        let path = test_dir("default-config").join("config.toml");
+        Config::load(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), DEFAULT_CONFIG);
+    }
+}
@@ -0,0 +1,434 @@
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Face {
+    U,
+    D,
+    R,
+    L,
+    F,
+    B
+}
+
+impl Face {
+    pub const ALL: [Face; 6] = [Face::U, Face::D, Face::F, Face::B, Face::R, Face::L];
+
+    pub fn colour_name(self) -> &'static str {
+        match self {
+            Face::U => "white",
+            Face::D => "yellow",
+            Face::F => "green",
+            Face::B => "blue",
+            Face::R => "red",
+            Face::L => "orange",
+        }
+    }
+
+    pub fn opposite(self) -> Face {
+        match self {
+            Face::U => Face::D,
+            Face::D => Face::U,
+            Face::R => Face::L,
+            Face::L => Face::R,
+            Face::F => Face::B,
+            Face::B => Face::F,
+        }
+    }
+
+    fn index(self) -> usize {
+        Face::ALL.iter().position(|&f| f == self).unwrap()
+    }
+
+    fn letter(self) -> char {
+        match self {
+            Face::U => 'U',
+            Face::D => 'D',
+            Face::R => 'R',
+            Face::L => 'L',
+            Face::F => 'F',
+            Face::B => 'B',
+        }
+    }
+
+    fn from_letter(c: char) -> Option<Face> {
+        match c.to_ascii_uppercase() {
+            'U' => Some(Face::U),
+            'D' => Some(Face::D),
+            'R' => Some(Face::R),
+            'L' => Some(Face::L),
+            'F' => Some(Face::F),
+            'B' => Some(Face::B),
+            _ => None
+        }
+    }
+
+    fn normal(self) -> [i32; 3] {
+        match self {
+            Face::U => [0, 1, 0],
+            Face::D => [0, -1, 0],
+            Face::R => [1, 0, 0],
+            Face::L => [-1, 0, 0],
+            Face::F => [0, 0, 1],
+            Face::B => [0, 0, -1],
+        }
+    }
+
+    fn from_normal(normal: [i32; 3]) -> Face {
+        *Face::ALL.iter().find(|f| f.normal() == normal).unwrap()
+    }
+
+    // Unit vectors pointing right and down when looking straight at the face
+    // in the usual net orientation (U and D seen with F towards the middle).
+    fn axes(self) -> ([i32; 3], [i32; 3]) {
+        match self {
+            Face::U => ([1, 0, 0], [0, 0, 1]),
+            Face::D => ([1, 0, 0], [0, 0, -1]),
+            Face::F => ([1, 0, 0], [0, -1, 0]),
+            Face::B => ([-1, 0, 0], [0, -1, 0]),
+            Face::R => ([0, 0, -1], [0, -1, 0]),
+            Face::L => ([0, 0, 1], [0, -1, 0]),
+        }
+    }
+
+    // Clockwise quarter turn, as seen looking at this face.
+    fn quarter_turn(self, [x, y, z]: [i32; 3]) -> [i32; 3] {
+        match self {
+            Face::R => [x, z, -y],
+            Face::L => [x, -z, y],
+            Face::U => [-z, y, x],
+            Face::D => [z, y, -x],
+            Face::F => [y, -x, z],
+            Face::B => [-y, x, z],
+        }
+    }
+}
+
+impl fmt::Display for Face {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.letter())
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum MoveError {
+    #[error("Invalid move: {0}")]
+    InvalidMove(String),
+    #[error("Move {0} cannot be applied to a {1}x{1} cube")]
+    InvalidLayers(String, usize)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layers {
+    Outer,
+    Wide(usize),
+    Slice(usize),
+    Middle,
+    All
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub face: Face,
+    pub layers: Layers,
+    pub turns: u8
+}
+
+impl Move {
+    pub fn outer(face: Face, turns: u8) -> Self {
+        Move { face, layers: Layers::Outer, turns: turns % 4 }
+    }
+
+    pub fn parse(token: &str) -> Result<Move, MoveError> {
+        let invalid = || MoveError::InvalidMove(token.to_string());
+
+        let (body, turns) = if let Some(body) = token.strip_suffix("2'") {
+            (body, 2)
+        } else if let Some(body) = token.strip_suffix('2') {
+            (body, 2)
+        } else if let Some(body) = token.strip_suffix('\'') {
+            (body, 3)
+        } else {
+            (token, 1)
+        };
+
+        let digits: String = body.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let rest = &body[digits.len()..];
+        let depth = if digits.is_empty() { None } else { Some(digits.parse::<usize>().map_err(|_| invalid())?) };
+
+        let mut chars = rest.chars();
+        let letter = chars.next().ok_or_else(invalid)?;
+        let wide = match chars.as_str() {
+            "" => false,
+            "w" => true,
+            _ => return Err(invalid())
+        };
+
+        let (face, layers) = match letter {
+            'x' => (Face::R, Layers::All),
+            'y' => (Face::U, Layers::All),
+            'z' => (Face::F, Layers::All),
+            'M' => (Face::L, Layers::Middle),
+            'E' => (Face::D, Layers::Middle),
+            'S' => (Face::F, Layers::Middle),
+            c if c.is_ascii_lowercase() => (Face::from_letter(c).ok_or_else(invalid)?, Layers::Wide(2)),
+            c => {
+                let face = Face::from_letter(c).ok_or_else(invalid)?;
+                let layers = match (depth, wide) {
+                    (None, false) => Layers::Outer,
+                    (None, true) => Layers::Wide(2),
+                    (Some(d), true) => Layers::Wide(d),
+                    (Some(d), false) => Layers::Slice(d),
+                };
+                (face, layers)
+            }
+        };
+
+        let plain_letter = matches!(layers, Layers::Outer | Layers::Wide(_) | Layers::Slice(_)) && letter.is_ascii_uppercase();
+        if (depth.is_some() || wide) && !plain_letter {
+            return Err(invalid());
+        }
+        if matches!(depth, Some(0)) {
+            return Err(invalid());
+        }
+
+        Ok(Move { face, layers, turns })
+    }
+
+    #[cfg(test)]
+    pub fn inverse(&self) -> Move {
+        Move { turns: (4 - self.turns) % 4, ..*self }
+    }
+
+    // Where `face` ends up when this move is applied as a whole-cube rotation.
+    pub(crate) fn map_face(&self, face: Face) -> Face {
+        let mut normal = face.normal();
+        for _ in 0..self.turns {
+            normal = self.face.quarter_turn(normal);
+        }
+        Face::from_normal(normal)
+    }
+
+    fn layer_range(&self, size: usize) -> Option<(usize, usize)> {
+        let range = match self.layers {
+            Layers::Outer => (1, 1),
+            Layers::Wide(depth) => (1, depth),
+            Layers::Slice(depth) => (depth, depth),
+            Layers::Middle if size % 2 == 1 => (size.div_ceil(2), size.div_ceil(2)),
+            Layers::Middle => return None,
+            Layers::All => (1, size),
+        };
+        if range.1 > size { None } else { Some(range) }
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.layers {
+            Layers::Outer => write!(f, "{}", self.face)?,
+            Layers::Wide(2) => write!(f, "{}w", self.face)?,
+            Layers::Wide(depth) => write!(f, "{}{}w", depth, self.face)?,
+            Layers::Slice(depth) => write!(f, "{}{}", depth, self.face)?,
+            Layers::Middle => write!(f, "{}", match self.face {
+                Face::L | Face::R => 'M',
+                Face::D | Face::U => 'E',
+                Face::F | Face::B => 'S',
+            })?,
+            Layers::All => write!(f, "{}", match self.face {
+                Face::R | Face::L => 'x',
+                Face::U | Face::D => 'y',
+                Face::F | Face::B => 'z',
+            })?,
+        }
+
+        match self.turns {
+            2 => write!(f, "2"),
+            3 => write!(f, "'"),
+            _ => Ok(())
+        }
+    }
+}
+
+pub fn parse_alg(alg: &str) -> Result<Vec<Move>, MoveError> {
+    alg.split_whitespace().map(Move::parse).collect()
+}
+
+pub fn format_alg(moves: &[Move]) -> String {
+    moves.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cube {
+    size: usize,
+    facelets: Vec<Face>
+}
+
+impl Cube {
+    pub fn new(size: usize) -> Self {
+        let facelets = Face::ALL.iter()
+            .flat_map(|&face| std::iter::repeat_n(face, size * size))
+            .collect();
+
+        Cube { size, facelets }
+    }
+
+    pub fn facelet(&self, face: Face, row: usize, col: usize) -> Face {
+        self.facelets[face.index() * self.size * self.size + row * self.size + col]
+    }
+
+    pub fn facelets(&self) -> &[Face] {
+        &self.facelets
+    }
+
+    pub fn apply(&mut self, m: &Move) -> Result<(), MoveError> {
+        let perm = self.permutation(m)?;
+        let mut facelets = self.facelets.clone();
+        for (from, &to) in perm.iter().enumerate() {
+            facelets[to] = self.facelets[from];
+        }
+        self.facelets = facelets;
+        Ok(())
+    }
+
+    pub fn apply_moves(&mut self, moves: &[Move]) -> Result<(), MoveError> {
+        moves.iter().try_for_each(|m| self.apply(m))
+    }
+
+    pub fn apply_alg(&mut self, alg: &str) -> Result<(), MoveError> {
+        self.apply_moves(&parse_alg(alg)?)
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.facelets
+            .chunks(self.size * self.size)
+            .all(|face| face.iter().all(|&f| f == face[0]))
+    }
+
+    // Where each facelet index ends up after the move.
+    pub fn permutation(&self, m: &Move) -> Result<Vec<usize>, MoveError> {
+        let (first, last) = m.layer_range(self.size)
+            .ok_or_else(|| MoveError::InvalidLayers(m.to_string(), self.size))?;
+
+        let normal = m.face.normal();
+        let n = self.size as i32;
+
+        Ok((0..self.facelets.len())
+            .map(|index| {
+                let (mut pos, mut face) = self.sticker(index);
+                let depth = pos.iter().zip(normal).map(|(a, b)| a * b).sum::<i32>();
+                let layer = ((n - 1 - depth) / 2 + 1) as usize;
+
+                if layer >= first && layer <= last {
+                    for _ in 0..m.turns {
+                        pos = m.face.quarter_turn(pos);
+                        face = Face::from_normal(m.face.quarter_turn(face.normal()));
+                    }
+                }
+                self.index_of(pos, face)
+            })
+            .collect())
+    }
+
+    // Position of the cubie holding the sticker (coordinates doubled so that
+    // they stay integral on even cubes) together with the face it lies on.
+    pub(crate) fn sticker(&self, index: usize) -> ([i32; 3], Face) {
+        let n = self.size;
+        let face = Face::ALL[index / (n * n)];
+        let row = ((index % (n * n)) / n) as i32;
+        let col = (index % n) as i32;
+        let offset = n as i32 - 1;
+
+        let normal = face.normal();
+        let (right, down) = face.axes();
+        let pos = [0, 1, 2].map(|i| {
+            normal[i] * offset + right[i] * (2 * col - offset) + down[i] * (2 * row - offset)
+        });
+
+        (pos, face)
+    }
+
+    pub(crate) fn index_of(&self, pos: [i32; 3], face: Face) -> usize {
+        let n = self.size;
+        let offset = n as i32 - 1;
+        let (right, down) = face.axes();
+        let dot = |v: [i32; 3]| pos.iter().zip(v).map(|(a, b)| a * b).sum::<i32>();
+
+        let col = ((dot(right) + offset) / 2) as usize;
+        let row = ((dot(down) + offset) / 2) as usize;
+        face.index() * n * n + row * n + col
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_format_moves() {
+        for token in ["R", "U'", "F2", "Rw", "Uw'", "3Fw2", "x", "y'", "z2", "M", "E'", "S2", "2R"] {
+            assert_eq!(Move::parse(token).unwrap().to_string(), token);
+        }
+
+        assert_eq!(Move::parse("r").unwrap().to_string(), "Rw");
+        assert_eq!(Move::parse("R2'").unwrap().to_string(), "R2");
+        assert!(Move::parse("Q").is_err());
+        assert!(Move::parse("xw").is_err());
+        assert!(Move::parse("R3").is_err());
+    }
+
+    #[test]
+    fn new_cube_is_solved() {
+        assert!(Cube::new(2).is_solved());
+        assert!(Cube::new(3).is_solved());
+        assert!(Cube::new(4).is_solved());
+    }
+
+    #[test]
+    fn sexy_move_has_order_six() {
+        let mut cube = Cube::new(3);
+        for i in 1..=6 {
+            cube.apply_alg("R U R' U'").unwrap();
+            assert_eq!(cube.is_solved(), i == 6);
+        }
+    }
+
+    #[test]
+    fn move_followed_by_inverse_is_identity() {
+        let mut cube = Cube::new(5);
+        let moves = parse_alg("R 2Uw' 3Fw2 L x y' M S E2 B'").unwrap();
+        cube.apply_moves(&moves).unwrap();
+        assert!(!cube.is_solved());
+
+        let inverse: Vec<_> = moves.iter().rev().map(Move::inverse).collect();
+        cube.apply_moves(&inverse).unwrap();
+        assert!(cube.is_solved());
+    }
+
+    #[test]
+    fn rotations_keep_the_cube_solved() {
+        let mut cube = Cube::new(3);
+        cube.apply_alg("x y z2 Rw L'").unwrap();
+        assert!(cube.is_solved());
+    }
+
+    #[test]
+    fn moves_cycle_the_expected_faces() {
+        let mut cube = Cube::new(3);
+        cube.apply_alg("R").unwrap();
+        assert_eq!(cube.facelet(Face::U, 0, 2), Face::F);
+        assert_eq!(cube.facelet(Face::B, 2, 0), Face::U);
+
+        let mut cube = Cube::new(3);
+        cube.apply_alg("U").unwrap();
+        assert_eq!(cube.facelet(Face::L, 0, 1), Face::F);
+    }
+
+    #[test]
+    fn invalid_layers_for_cube_size() {
+        let mut cube = Cube::new(3);
+        assert!(matches!(cube.apply_alg("4Rw"), Err(MoveError::InvalidLayers(..))));
+
+        let mut cube = Cube::new(4);
+        assert!(matches!(cube.apply_alg("M"), Err(MoveError::InvalidLayers(..))));
+    }
+}
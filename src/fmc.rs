@@ -0,0 +1,86 @@
+use crate::cube::{parse_alg, Cube, Layers, MoveError};
+
+use std::time::Duration;
+use thiserror::Error;
+
+pub const TIME_LIMIT: Duration = Duration::from_secs(60 * 60);
+pub const MAX_MOVES: u32 = 80;
+pub const SCRAMBLE_PADDING: &str = "R' U' F";
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FmcError {
+    #[error(transparent)]
+    InvalidMove(#[from] MoveError),
+    #[error("Slice moves are not allowed: {0}")]
+    SliceMove(String),
+    #[error("Solution is longer than {MAX_MOVES} moves")]
+    TooLong,
+    #[error("Solution does not solve the cube")]
+    NotSolved
+}
+
+// Checks the solution against the scramble and returns its length in the
+// outer block turn metric, where rotations are free.
+pub fn verify(scramble: &str, solution: &str) -> Result<u32, FmcError> {
+    let moves = parse_alg(solution)?;
+
+    if let Some(slice) = moves.iter().find(|m| matches!(m.layers, Layers::Middle | Layers::Slice(_) | Layers::Wide(3..))) {
+        return Err(FmcError::SliceMove(slice.to_string()));
+    }
+
+    let count = moves.iter().filter(|m| m.layers != Layers::All).count() as u32;
+    if count > MAX_MOVES {
+        return Err(FmcError::TooLong);
+    }
+
+    let mut cube = Cube::new(3);
+    cube.apply_alg(scramble)?;
+    cube.apply_moves(&moves)?;
+
+    if cube.is_solved() { Ok(count) } else { Err(FmcError::NotSolved) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRAMBLE: &str = "R' U' F R U R' U' F' U2 R' U' F";
+
+    #[test]
+    fn valid_solution() {
+        assert_eq!(verify(SCRAMBLE, "F' U R U2 F U R U' R' F' U R"), Ok(12));
+    }
+
+    #[test]
+    fn rotations_are_free() {
+        assert_eq!(verify("R U", "y U' y' R'"), Ok(2));
+        assert_eq!(verify("R U", "y U' F' y'"), Ok(2));
+    }
+
+    #[test]
+    fn wide_moves_count_as_one() {
+        assert_eq!(verify("Rw", "Rw'"), Ok(1));
+        assert_eq!(verify("Rw", "L' x'"), Ok(1));
+    }
+
+    #[test]
+    fn unsolved_solution_is_rejected() {
+        assert_eq!(verify(SCRAMBLE, "F' U F"), Err(FmcError::NotSolved));
+    }
+
+    #[test]
+    fn slice_moves_are_rejected() {
+        assert_eq!(verify("M", "M'"), Err(FmcError::SliceMove("M'".to_string())));
+    }
+
+    #[test]
+    fn invalid_notation_is_rejected() {
+        assert!(matches!(verify(SCRAMBLE, "R U Q"), Err(FmcError::InvalidMove(_))));
+    }
+
+    #[test]
+    fn long_solutions_are_rejected() {
+        let solution = vec!["R U R' U'"; 21].join(" ");
+        assert_eq!(verify("", &solution), Err(FmcError::TooLong));
+    }
+}
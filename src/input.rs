@@ -0,0 +1,88 @@
+use std::io;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Instant;
+use ratatui::crossterm::event::{Event, KeyCode, KeyEventKind};
+
+#[derive(Debug, Clone, Copy)]
+pub struct KeyPress {
+    pub code: KeyCode,
+    pub at: Instant
+}
+
+// Reads events on their own thread so each key press is timestamped as soon
+// as it arrives, instead of after the next draw and poll. The thread stops
+// after a read error, which is sent on, or when the receiver is dropped.
+pub fn spawn<R>(mut read: R) -> Receiver<io::Result<KeyPress>>
+where
+    R: FnMut() -> io::Result<Event> + Send + 'static
+{
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || loop {
+        let press = match read() {
+            Ok(Event::Key(event)) if event.kind == KeyEventKind::Press => Ok(KeyPress { code: event.code, at: Instant::now() }),
+            Ok(_) => continue,
+            Err(error) => Err(error),
+        };
+        let failed = press.is_err();
+        if sender.send(press).is_err() || failed {
+            return;
+        }
+    });
+
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::{SystemClock, Timer};
+    use ratatui::crossterm::event::{KeyEvent, KeyModifiers};
+    use std::time::Duration;
+
+    const PRESS_INTERVAL: Duration = Duration::from_millis(30);
+    const DRAW_TIME: Duration = Duration::from_millis(300);
+    // Timing keys as they are handled would be out by nearly a whole draw,
+    // so half of one leaves room for a loaded machine while still telling
+    // the two apart.
+    const MAX_ERROR: Duration = Duration::from_millis(150);
+
+    // Presses space every `PRESS_INTERVAL`, reporting when each happened.
+    fn scripted_presses() -> (Receiver<io::Result<KeyPress>>, Receiver<Instant>) {
+        let (pressed_sender, pressed) = mpsc::channel();
+        let keys = spawn(move || {
+            thread::sleep(PRESS_INTERVAL);
+            let _ = pressed_sender.send(Instant::now());
+            Ok(Event::Key(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE)))
+        });
+        (keys, pressed)
+    }
+
+    #[test]
+    fn skips_other_events() {
+        let mut events = vec![Event::FocusGained, Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))].into_iter();
+        let keys = spawn(move || events.next().ok_or_else(|| io::Error::other("closed")));
+
+        assert_eq!(keys.recv().unwrap().unwrap().code, KeyCode::Enter);
+        assert!(keys.recv().unwrap().is_err());
+        assert!(keys.recv().is_err());
+    }
+
+    // Even when each key is only handled after a slow draw, the timed
+    // interval stays within `MAX_ERROR` of when the keys were pressed.
+    #[test]
+    fn timing_error_is_bounded_by_capture_not_drawing() {
+        let (keys, pressed) = scripted_presses();
+        let mut timer = Timer::new(Box::new(SystemClock));
+
+        thread::sleep(DRAW_TIME);
+        timer.start_at(keys.recv().unwrap().unwrap().at);
+        thread::sleep(DRAW_TIME);
+        timer.pause_at(keys.recv().unwrap().unwrap().at);
+
+        let first = pressed.recv().unwrap();
+        let actual = pressed.recv().unwrap() - first;
+        assert!(timer.elapsed().abs_diff(actual) < MAX_ERROR, "timed {:?}, pressed {:?} apart", timer.elapsed(), actual);
+    }
+}
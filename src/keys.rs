@@ -0,0 +1,245 @@
+use std::fmt;
+use ratatui::crossterm::event::KeyCode;
+use serde::Deserialize;
+
+// What a key does. Most act on the timer screen, and the screens they open
+// close with the same key; the rest act within one of those screens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    StartStop,
+    Quit,
+    SolveDetail,
+    NextPuzzle,
+    SplitMode,
+    HardScrambles,
+    Precision,
+    LargeDigits,
+    Layout,
+    ManualEntry,
+    Target,
+    Filter,
+    Report,
+    Compare,
+    Rankings,
+    Profile,
+    Help,
+    Command,
+    Tags,
+    Export,
+    DateRange
+}
+
+impl Action {
+    pub const ALL: [Action; 21] = [
+        Action::StartStop,
+        Action::Quit,
+        Action::SolveDetail,
+        Action::NextPuzzle,
+        Action::SplitMode,
+        Action::HardScrambles,
+        Action::Precision,
+        Action::LargeDigits,
+        Action::Layout,
+        Action::ManualEntry,
+        Action::Target,
+        Action::Filter,
+        Action::Report,
+        Action::Compare,
+        Action::Rankings,
+        Action::Profile,
+        Action::Help,
+        Action::Command,
+        Action::Tags,
+        Action::Export,
+        Action::DateRange
+    ];
+
+    fn default_key(&self) -> KeyCode {
+        KeyCode::Char(match self {
+            Action::StartStop => ' ',
+            Action::Quit => 'q',
+            Action::SolveDetail => 'd',
+            Action::NextPuzzle => return KeyCode::Tab,
+            Action::SplitMode => 's',
+            Action::HardScrambles => 'h',
+            Action::Precision => 'p',
+            Action::LargeDigits => 'l',
+            Action::Layout => 'v',
+            Action::ManualEntry => 'm',
+            Action::Target => 't',
+            Action::Filter => '/',
+            Action::Report => 'r',
+            Action::Compare => 'c',
+            Action::Rankings => 'w',
+            Action::Profile => 'a',
+            Action::Help => '?',
+            Action::Command => ':',
+            Action::Tags => 'g',
+            Action::Export => 'e',
+            Action::DateRange => 'f',
+        })
+    }
+
+    // Whether the action works within the solve detail, report or compare
+    // screen rather than on the timer screen.
+    pub fn in_screen(&self) -> bool {
+        matches!(self, Action::Tags | Action::Export | Action::DateRange)
+    }
+
+    // What the action does, as listed in the help.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::StartStop => "start or stop the timer",
+            Action::Quit => "save and quit",
+            Action::SolveDetail => "show the last solve",
+            Action::NextPuzzle => "next puzzle",
+            Action::SplitMode => "toggle split mode",
+            Action::HardScrambles => "toggle hard scrambles",
+            Action::Precision => "change timer precision",
+            Action::LargeDigits => "toggle large digits",
+            Action::Layout => "change layout",
+            Action::ManualEntry => "toggle manual entry",
+            Action::Target => "set a target time",
+            Action::Filter => "filter solves",
+            Action::Report => "daily report",
+            Action::Compare => "compare sessions",
+            Action::Rankings => "WCA rankings",
+            Action::Profile => "profile",
+            Action::Help => "show this help",
+            Action::Command => "open the command palette",
+            Action::Tags => "edit the shown solve's tags",
+            Action::Export => "export the daily report",
+            Action::DateRange => "set a compared side's dates",
+        }
+    }
+}
+
+// Keys are written as a single character or one of the names below, such as
+// `space` or `f1`.
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+
+    Some(match name.to_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "tab" => KeyCode::Tab,
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+            Some(n @ 1..=12) => KeyCode::F(n),
+            _ => return None,
+        },
+    })
+}
+
+// Keys that typing modes and dialogs rely on, which no action may take.
+pub fn is_reserved(key: KeyCode) -> bool {
+    matches!(key, KeyCode::Esc | KeyCode::Enter | KeyCode::Backspace) || matches!(key, KeyCode::Char(c) if c.is_ascii_digit())
+}
+
+pub struct KeyName(pub KeyCode);
+
+impl fmt::Display for KeyName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            code => write!(f, "{}", format!("{:?}", code).to_lowercase()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(Action, KeyCode)>
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap { bindings: Action::ALL.iter().map(|action| (*action, action.default_key())).collect() }
+    }
+}
+
+impl Keymap {
+    // Rebinds actions, failing with the key when two actions would share it.
+    pub fn with_bindings(mut self, bindings: impl IntoIterator<Item = (Action, KeyCode)>) -> Result<Keymap, KeyCode> {
+        for (action, key) in bindings {
+            if let Some(binding) = self.bindings.iter_mut().find(|(a, _)| *a == action) {
+                binding.1 = key;
+            }
+        }
+
+        for (i, (_, key)) in self.bindings.iter().enumerate() {
+            if self.bindings[..i].iter().any(|(_, other)| other == key) {
+                return Err(*key);
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        self.bindings.iter().find(|(_, k)| *k == key).map(|(action, _)| *action)
+    }
+
+    // Every action starts out bound and rebinding only moves it.
+    pub fn key(&self, action: Action) -> KeyCode {
+        self.bindings.iter().find(|(a, _)| *a == action).map(|(_, key)| *key).expect("every action is bound")
+    }
+
+    pub fn bindings(&self) -> &[(Action, KeyCode)] {
+        &self.bindings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_keys() {
+        assert_eq!(parse_key("x"), Some(KeyCode::Char('x')));
+        assert_eq!(parse_key("X"), Some(KeyCode::Char('X')));
+        assert_eq!(parse_key("Space"), Some(KeyCode::Char(' ')));
+        assert_eq!(parse_key("f5"), Some(KeyCode::F(5)));
+        assert_eq!(parse_key("f13"), None);
+        assert_eq!(parse_key("hyper"), None);
+        assert_eq!(parse_key(""), None);
+
+        for key in Action::ALL.map(|action| action.default_key()) {
+            assert_eq!(parse_key(&KeyName(key).to_string()), Some(key));
+        }
+    }
+
+    #[test]
+    fn rebinding() {
+        let keymap = Keymap::default().with_bindings([(Action::StartStop, KeyCode::Enter)]).unwrap();
+        assert_eq!(keymap.action(KeyCode::Enter), Some(Action::StartStop));
+        assert_eq!(keymap.action(KeyCode::Char(' ')), None);
+        assert_eq!(keymap.action(KeyCode::Char('q')), Some(Action::Quit));
+        assert_eq!(keymap.key(Action::StartStop), KeyCode::Enter);
+        assert_eq!(keymap.key(Action::Export), KeyCode::Char('e'));
+
+        assert!(is_reserved(KeyCode::Esc));
+        assert!(is_reserved(KeyCode::Char('7')));
+        assert!(!is_reserved(KeyCode::Char('x')));
+
+        let duplicate = Keymap::default().with_bindings([(Action::Report, KeyCode::Char('q'))]);
+        assert_eq!(duplicate, Err(KeyCode::Char('q')));
+    }
+}
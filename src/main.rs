@@ -1,20 +1,40 @@
 mod app;
+mod command;
+mod compare;
+mod config;
+mod cube;
+mod fmc;
+mod input;
+mod keys;
 mod math;
+mod mbld;
+mod profile;
+mod query;
+mod report;
 mod scramble;
 mod session;
 mod storage;
 mod solve;
+mod solver;
+mod stats;
+mod stackmat;
+mod theme;
 mod timer;
 mod ui;
+mod wca;
 
 use crate::app::App;
-use crate::storage::FileSystemStorage;
+use crate::config::{Config, ConfigError, StorageBackend};
+use crate::storage::{FileSystemStorage, MemoryStorage};
 use crate::solve::SolveError;
+use crate::stackmat::{Packet, StackmatError, WavSource};
+use crate::storage::Storage;
+use crate::wca::{RankIndex, WcaError};
 
-use std::{result, io::{Stdout, stdout}, time::Duration};
+use std::{env, path::{Path, PathBuf}, process, result, io::{Stdout, stdout}, sync::mpsc::{Receiver, RecvTimeoutError}, time::Duration};
 use ratatui::{
     crossterm::{
-        event::{self, Event, KeyEventKind}, execute, terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen}
+        event, execute, terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen}
     }, prelude::*, Terminal
 };
 use thiserror::Error;
@@ -29,22 +49,72 @@ pub enum CubeError {
     #[error("Solve error: {0}")]
     InvalidSolve(#[from] SolveError),
     #[error("Serialization error: {0}")]
-    Serde(#[from] serde_json::Error)
+    Serde(#[from] serde_json::Error),
+    #[error("Stackmat error: {0}")]
+    Stackmat(#[from] StackmatError),
+    #[error("WCA export error: {0}")]
+    Wca(#[from] WcaError),
+    #[error("Config error in {0}: {1}")]
+    Config(PathBuf, ConfigError),
+    #[error("{0} needs {1}")]
+    MissingArgument(&'static str, &'static str)
 }
 
 pub type Result<T> = result::Result<T, CubeError>;
 
-pub fn main() -> Result<()> {
-    let mut terminal = setup_terminal()?;
+pub fn main() {
+    if let Err(error) = run() {
+        eprintln!("Error: {error}");
+        process::exit(1);
+    }
+}
 
-    let storage = FileSystemStorage::build()?;
-    let app = App::build(Box::new(storage))?;
+fn run() -> Result<()> {
+    // `--stackmat <file.wav>` times solves from a Stackmat recording,
+    // `--wca-import <dir>` indexes an unpacked WCA results export and
+    // `--country <id>` picks the country to rank in.
+    let args: Vec<String> = env::args().collect();
+    let value = |flag: &'static str, what: &'static str| match args.iter().position(|arg| arg == flag) {
+        Some(index) => match args.get(index + 1) {
+            Some(value) if !value.starts_with("--") => Ok(Some(value.as_str())),
+            _ => Err(CubeError::MissingArgument(flag, what)),
+        },
+        None => Ok(None),
+    };
+
+    let config_path = storage::config_path()?;
+    let config = Config::load(&config_path).map_err(|error| CubeError::Config(config_path, error))?;
+    let storage: Box<dyn Storage> = match config.storage {
+        StorageBackend::File => Box::new(FileSystemStorage::build()?),
+        StorageBackend::Memory => Box::new(MemoryStorage::default()),
+    };
+
+    if let Some(dir) = value("--wca-import", "a directory")? {
+        let index = RankIndex::import(Path::new(dir))?;
+        storage.save_rank_index(&index)?;
+        println!("Imported rankings for {} events", index.event_count());
+        return Ok(());
+    }
+
+    let packets = match value("--stackmat", "a WAV file")? {
+        Some(path) => Some(stackmat::spawn(Box::new(WavSource::open(Path::new(path))?))),
+        None => None
+    };
 
-    run_app(app, &mut terminal)?;
+    // The app is built before the terminal is taken over, so an error
+    // building it leaves the terminal as it was.
+    let mut app = App::build(storage, config)?;
+    if let Some(country) = value("--country", "a country id")? {
+        app.country = Some(country.to_string());
+    }
 
+    let mut terminal = setup_terminal()?;
+
+    // The terminal is restored whatever happened to the app.
+    let result = run_app(app, &mut terminal, packets);
     restore_terminal(&mut terminal)?;
 
-    Ok(())
+    result
 }
 
 fn setup_terminal() -> Result<Tui> {
@@ -60,16 +130,24 @@ fn setup_terminal() -> Result<Tui> {
 }
 
 
-fn run_app(mut app: App, terminal: &mut Tui) -> Result<()> {
+fn run_app(mut app: App, terminal: &mut Tui, packets: Option<Receiver<Packet>>) -> Result<()> {
+    let keys = input::spawn(event::read);
+
     while !app.should_quit {
         terminal.draw(|frame| ui::draw(frame, &app))?;
 
-        if event::poll(Duration::from_millis(16))? {
-            if let Event::Key(event) = event::read()? {
-                if event.kind == KeyEventKind::Press {
-                    app.on_key_pressed(event.code)?
-                }
-            }
+        for packet in packets.iter().flat_map(|packets| packets.try_iter()) {
+            app.on_stackmat_packet(packet)?;
+        }
+        app.on_tick()?;
+
+        match keys.recv_timeout(Duration::from_millis(16)) {
+            Ok(press) => {
+                let press = press?;
+                app.on_key_pressed(press.code, press.at)?;
+            },
+            Err(RecvTimeoutError::Timeout) => {},
+            Err(RecvTimeoutError::Disconnected) => break,
         }
     }
     Ok(())
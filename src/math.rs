@@ -1,3 +1,4 @@
+use std::f64::consts::PI;
 use std::time::Duration;
 
 pub fn avg(times: &[Duration]) -> Option<Duration> {
@@ -9,19 +10,365 @@ pub fn avg(times: &[Duration]) -> Option<Duration> {
     Some(sum / times.len() as u32)
 }
 
-pub fn std(times: &[Duration]) -> Option<Duration> {
-    let avg = avg(&times)?;
+// Results are given as `None` for DNFs. The best and worst 5% of results
+// (at least one each) are dropped, DNFs counting as the worst.
+pub fn trimmed_avg(results: &[Option<Duration>]) -> Option<Duration> {
+    if results.is_empty() {
+        return None
+    }
+
+    trimmed_mean(results, 5)
+}
+
+// Mean after dropping `percent`% of results (rounded up) from each end,
+// DNFs counting as the worst.
+pub fn trimmed_mean(results: &[Option<Duration>], percent: usize) -> Option<Duration> {
+    sorted_trimmed_mean(&sorted(results), percent)
+}
+
+// DNFs rank slowest.
+pub fn rank(result: &Option<Duration>) -> Duration {
+    result.unwrap_or(Duration::MAX)
+}
+
+fn sorted(results: &[Option<Duration>]) -> Vec<Option<Duration>> {
+    let mut sorted = results.to_vec();
+    sorted.sort_by_key(rank);
+    sorted
+}
+
+// `trimmed_mean` of results already sorted by `rank`.
+pub fn sorted_trimmed_mean(sorted: &[Option<Duration>], percent: usize) -> Option<Duration> {
+    let trim = (sorted.len() * percent).div_ceil(100);
+    if sorted.len() <= 2 * trim {
+        return None
+    }
+
+    mean(&sorted[trim..sorted.len() - trim])
+}
+
+// Functions only built for tests are the reference that the session's
+// cached stats are checked against.
+
+// Percentile `p` (0.0 to 1.0) interpolated between the nearest ranks, with
+// DNFs ranked slowest. DNF when the percentile depends on a DNF.
+#[cfg(test)]
+pub fn percentile(results: &[Option<Duration>], p: f64) -> Option<Duration> {
+    sorted_percentile(&sorted(results), p)
+}
+
+// `percentile` of results already sorted by `rank`.
+pub fn sorted_percentile(sorted: &[Option<Duration>], p: f64) -> Option<Duration> {
+    if sorted.is_empty() || !(0.0..=1.0).contains(&p) {
+        return None
+    }
+
+    let rank = (sorted.len() - 1) as f64 * p;
+    let lower = rank.floor() as usize;
+    let fraction = rank - lower as f64;
+    if fraction == 0.0 {
+        return sorted[lower]
+    }
+
+    let (low, high) = (sorted[lower]?, sorted[lower + 1]?);
+    Some(low + (high - low).mul_f64(fraction))
+}
+
+#[cfg(test)]
+pub fn median(results: &[Option<Duration>]) -> Option<Duration> {
+    percentile(results, 0.5)
+}
+
+#[cfg(test)]
+pub fn iqr(results: &[Option<Duration>]) -> Option<Duration> {
+    Some(percentile(results, 0.75)? - percentile(results, 0.25)?)
+}
+
+// Standard deviations and the coefficient of variation are in seconds and
+// describe the spread of completed solves, so DNFs are left out.
+#[cfg(test)]
+pub fn population_std(results: &[Option<Duration>]) -> Option<f64> {
+    let times = seconds(results);
+    if times.is_empty() {
+        return None
+    }
+    Some((squared_deviations(&times) / times.len() as f64).sqrt())
+}
 
-    let variance = times.iter()
-        .map(|&time| {
-            let diff = if time > avg { time - avg } else { avg - time };
-            diff.as_millis() * diff.as_millis()
+pub fn sample_std(results: &[Option<Duration>]) -> Option<f64> {
+    let times = seconds(results);
+    if times.len() < 2 {
+        return None
+    }
+    Some((squared_deviations(&times) / (times.len() - 1) as f64).sqrt())
+}
+
+#[cfg(test)]
+pub fn coefficient_of_variation(results: &[Option<Duration>]) -> Option<f64> {
+    let times = seconds(results);
+    let mean = times.iter().sum::<f64>() / times.len() as f64;
+    Some(sample_std(results)? / mean)
+}
+
+fn seconds(results: &[Option<Duration>]) -> Vec<f64> {
+    results.iter().flatten().map(Duration::as_secs_f64).collect()
+}
+
+fn squared_deviations(times: &[f64]) -> f64 {
+    let mean = times.iter().sum::<f64>() / times.len() as f64;
+    times.iter().map(|time| (time - mean).powi(2)).sum()
+}
+
+pub fn mean(results: &[Option<Duration>]) -> Option<Duration> {
+    let times: Option<Vec<Duration>> = results.iter().copied().collect();
+    avg(&times?)
+}
+
+pub fn best(results: &[Option<Duration>]) -> Option<Duration> {
+    results.iter().flatten().min().copied()
+}
+
+// Fraction of results faster than the target, DNFs counting as slower.
+#[cfg(test)]
+pub fn rate_below(results: &[Option<Duration>], target: Duration) -> Option<f64> {
+    if results.is_empty() {
+        return None
+    }
+
+    let below = results.iter().flatten().filter(|&&time| time < target).count();
+    Some(below as f64 / results.len() as f64)
+}
+
+pub fn mean_moves(results: &[Option<u32>]) -> Option<f64> {
+    let moves: Option<Vec<u32>> = results.iter().copied().collect();
+    let moves = moves?;
+    if moves.is_empty() {
+        return None
+    }
+
+    Some(moves.iter().sum::<u32>() as f64 / moves.len() as f64)
+}
+
+// Roughly a 95% band around a fitted trend.
+const BAND_DEVIATIONS: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trend {
+    Linear,
+    // A learning curve y = a * (x + 1)^b, fitted as a line on a log-log scale.
+    PowerLaw
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Regression {
+    pub trend: Trend,
+    pub intercept: f64,
+    pub slope: f64,
+    count: usize,
+    mean_x: f64,
+    sum_squares_x: f64,
+    residual_std: f64
+}
+
+// Least squares fit of `points` (x, y) in the scale of the trend. Needs at
+// least three distinct x values, and positive y values for a power law.
+pub fn regression(points: &[(f64, f64)], trend: Trend) -> Option<Regression> {
+    let points = points.iter()
+        .map(|&(x, y)| match trend {
+            Trend::Linear => Some((x, y)),
+            Trend::PowerLaw if x > -1.0 && y > 0.0 => Some(((x + 1.0).ln(), y.ln())),
+            Trend::PowerLaw => None,
         })
-        .sum::<u128>() / times.len() as u128;
+        .collect::<Option<Vec<_>>>()?;
+    if points.len() < 3 {
+        return None
+    }
+
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / count;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / count;
+    let sum_squares_x: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    if sum_squares_x == 0.0 {
+        return None
+    }
 
-    let std = (variance as f64).sqrt();
+    let slope = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum::<f64>() / sum_squares_x;
+    let intercept = mean_y - slope * mean_x;
+    let residuals: f64 = points.iter().map(|p| (p.1 - intercept - slope * p.0).powi(2)).sum();
 
-    Some(Duration::from_millis(std as u64))
+    Some(Regression {
+        trend,
+        intercept,
+        slope,
+        count: points.len(),
+        mean_x,
+        sum_squares_x,
+        residual_std: (residuals / (count - 2.0)).sqrt()
+    })
+}
+
+// Whichever trend has the smaller squared error on the original scale.
+pub fn best_regression(points: &[(f64, f64)]) -> Option<Regression> {
+    [Trend::Linear, Trend::PowerLaw]
+        .into_iter()
+        .filter_map(|trend| regression(points, trend))
+        .min_by(|a, b| a.squared_error(points).total_cmp(&b.squared_error(points)))
+}
+
+impl Regression {
+    fn scale_x(&self, x: f64) -> f64 {
+        match self.trend {
+            Trend::Linear => x,
+            Trend::PowerLaw => (x + 1.0).ln(),
+        }
+    }
+
+    fn unscale_y(&self, y: f64) -> f64 {
+        match self.trend {
+            Trend::Linear => y,
+            Trend::PowerLaw => y.exp(),
+        }
+    }
+
+    pub fn predict(&self, x: f64) -> f64 {
+        self.unscale_y(self.intercept + self.slope * self.scale_x(x))
+    }
+
+    // Lower and upper bounds of the confidence band for the trend at `x`.
+    pub fn band(&self, x: f64) -> (f64, f64) {
+        let scaled = self.scale_x(x);
+        let error = self.residual_std * (1.0 / self.count as f64 + (scaled - self.mean_x).powi(2) / self.sum_squares_x).sqrt();
+        let predicted = self.intercept + self.slope * scaled;
+        (
+            self.unscale_y(predicted - BAND_DEVIATIONS * error),
+            self.unscale_y(predicted + BAND_DEVIATIONS * error),
+        )
+    }
+
+    // The x at which the trend comes down to `goal`, if it is improving.
+    pub fn reaches(&self, goal: f64) -> Option<f64> {
+        if self.slope >= 0.0 {
+            return None
+        }
+
+        match self.trend {
+            Trend::Linear => Some((goal - self.intercept) / self.slope),
+            Trend::PowerLaw if goal > 0.0 => Some(((goal.ln() - self.intercept) / self.slope).exp() - 1.0),
+            Trend::PowerLaw => None,
+        }
+    }
+
+    fn squared_error(&self, points: &[(f64, f64)]) -> f64 {
+        points.iter().map(|&(x, y)| (y - self.predict(x)).powi(2)).sum()
+    }
+}
+
+// Counts of values in `bins` bins of `width` starting at `start`. Values
+// outside the bins are not counted.
+pub fn histogram(values: &[f64], start: f64, width: f64, bins: usize) -> Vec<usize> {
+    let mut counts = vec![0; bins];
+    for value in values {
+        let bin = ((value - start) / width).floor();
+        if bin >= 0.0 && (bin as usize) < bins {
+            counts[bin as usize] += 1;
+        }
+    }
+    counts
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WelchTest {
+    pub t: f64,
+    pub degrees_of_freedom: f64,
+    // Two-tailed probability of a difference at least this large if both
+    // samples had the same mean.
+    pub p: f64
+}
+
+// Welch's t-test for a difference in means, which does not assume equal
+// variances. Needs two values in each sample and some spread.
+pub fn welch_t_test(a: &[f64], b: &[f64]) -> Option<WelchTest> {
+    if a.len() < 2 || b.len() < 2 {
+        return None
+    }
+
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    let error = |values: &[f64]| squared_deviations(values) / (values.len() - 1) as f64 / values.len() as f64;
+    let (error_a, error_b) = (error(a), error(b));
+    if error_a + error_b == 0.0 {
+        return None
+    }
+
+    let t = (mean(a) - mean(b)) / (error_a + error_b).sqrt();
+    let degrees_of_freedom = (error_a + error_b).powi(2)
+        / (error_a.powi(2) / (a.len() - 1) as f64 + error_b.powi(2) / (b.len() - 1) as f64);
+    let p = incomplete_beta(degrees_of_freedom / 2.0, 0.5, degrees_of_freedom / (degrees_of_freedom + t * t));
+
+    Some(WelchTest { t, degrees_of_freedom, p })
+}
+
+// Lanczos approximation of ln Γ(x).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9, 676.520_368_121_885_1, -1_259.139_216_722_402_8,
+        771.323_428_777_653_1, -176.615_029_162_140_6, 12.507_343_278_686_905,
+        -0.138_571_095_265_720_12, 9.984_369_578_019_572e-6, 1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        return PI.ln() - (PI * x).sin().ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = COEFFICIENTS[1..].iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| sum + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+// Regularized incomplete beta function I_x(a, b).
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+// Continued fraction for the incomplete beta function, by Lentz's method.
+fn beta_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let clamp = |value: f64| if value.abs() < TINY { TINY } else { value };
+
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut fraction = d;
+    for m in 1..200 {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + even * d);
+        c = clamp(1.0 + even / c);
+        fraction *= d * c;
+
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / clamp(1.0 + odd * d);
+        c = clamp(1.0 + odd / c);
+        let delta = d * c;
+        fraction *= delta;
+        if (delta - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    fraction
 }
 
 #[cfg(test)]
@@ -54,29 +401,208 @@ mod tests {
         assert_eq!(avg(&times), Some(Duration::from_millis(6485)));
     }
 
+    fn assert_close(value: Option<f64>, expected: f64) {
+        let value = value.unwrap();
+        assert!((value - expected).abs() < 1e-9, "{} != {}", value, expected);
+    }
+
     #[test]
     fn std_with_empty_vector() {
-        let empty_vec = vec![];
-        assert_eq!(std(&empty_vec), None);
+        assert_eq!(population_std(&[]), None);
+        assert_eq!(sample_std(&[]), None);
+        assert_eq!(sample_std(&secs(&[10])), None);
+        assert_eq!(coefficient_of_variation(&[None]), None);
     }
 
     #[test]
     fn std_with_multiple_elements() {
-        let times = vec![
-            Duration::from_millis(10000),
-            Duration::from_millis(20000),
-            Duration::from_millis(30000),
-        ];
+        let times = secs(&[10, 20, 30]);
+        assert_close(population_std(&times), 8.16496580927726);
+        assert_close(sample_std(&times), 10.0);
+        assert_close(coefficient_of_variation(&times), 0.5);
 
-        assert_eq!(std(&times), Some(Duration::from_millis(8164)));
+        let times: Vec<_> = [2100, 4300, 13900, 5640].iter().map(|&ms| Some(Duration::from_millis(ms))).collect();
+        assert_close(population_std(&times), 4.463706419557631);
+        assert_close(sample_std(&times), 5.154244205830117);
+    }
 
-        let times = vec![
-            Duration::from_millis(2100),
-            Duration::from_millis(4300),
-            Duration::from_millis(13900),
-            Duration::from_millis(5640),
-        ];
+    #[test]
+    fn std_ignores_dnfs() {
+        let mut times = secs(&[10, 20, 30]);
+        times.push(None);
+        assert_close(population_std(&times), 8.16496580927726);
+        assert_close(sample_std(&times), 10.0);
+    }
+
+    fn secs(values: &[u64]) -> Vec<Option<Duration>> {
+        values.iter().map(|&s| Some(Duration::from_secs(s))).collect()
+    }
+
+    #[test]
+    fn trimmed_avg_drops_best_and_worst() {
+        assert_eq!(trimmed_avg(&secs(&[10, 12, 14, 30, 1])), Some(Duration::from_secs(12)));
+        assert_eq!(trimmed_avg(&[]), None);
+    }
+
+    #[test]
+    fn trimmed_avg_with_dnfs() {
+        let mut results = secs(&[10, 12, 14, 16]);
+        results.push(None);
+        assert_eq!(trimmed_avg(&results), Some(Duration::from_secs(14)));
+
+        results.push(None);
+        assert_eq!(trimmed_avg(&results[1..]), None);
+    }
+
+    #[test]
+    fn trimmed_mean_by_percent() {
+        let results = secs(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 100]);
+        assert_eq!(trimmed_mean(&results, 0), Some(Duration::from_millis(14500)));
+        assert_eq!(trimmed_mean(&results, 10), Some(Duration::from_millis(5500)));
+        assert_eq!(trimmed_mean(&results, 25), Some(Duration::from_millis(5500)));
+        assert_eq!(trimmed_mean(&results, 50), None);
+
+        let mut results = results;
+        results[9] = None;
+        assert_eq!(trimmed_mean(&results, 0), None);
+        assert_eq!(trimmed_mean(&results, 10), Some(Duration::from_millis(5500)));
+    }
+
+    #[test]
+    fn percentiles() {
+        let results = secs(&[15, 11, 13, 12, 14]);
+        assert_eq!(median(&results), Some(Duration::from_secs(13)));
+        assert_eq!(percentile(&results, 0.0), Some(Duration::from_secs(11)));
+        assert_eq!(percentile(&results, 1.0), Some(Duration::from_secs(15)));
+        assert_eq!(percentile(&results, 0.1), Some(Duration::from_millis(11400)));
+        assert_eq!(iqr(&results), Some(Duration::from_secs(2)));
+
+        assert_eq!(median(&secs(&[10, 20, 30, 40])), Some(Duration::from_secs(25)));
+        assert_eq!(percentile(&results, 1.5), None);
+        assert_eq!(median(&[]), None);
+    }
+
+    #[test]
+    fn percentiles_with_dnfs() {
+        let results = [Some(Duration::from_secs(10)), None, Some(Duration::from_secs(12)), None];
+        assert_eq!(percentile(&results, 0.0), Some(Duration::from_secs(10)));
+        assert_eq!(percentile(&results, 0.25), Some(Duration::from_millis(11500)));
+        assert_eq!(median(&results), None);
+        assert_eq!(iqr(&results), None);
+        assert_eq!(median(&results[..3]), Some(Duration::from_secs(12)));
+    }
+
+    #[test]
+    fn mean_with_dnf_is_dnf() {
+        assert_eq!(mean(&secs(&[30, 60, 90])), Some(Duration::from_secs(60)));
+        assert_eq!(mean(&[Some(Duration::from_secs(30)), None, Some(Duration::from_secs(60))]), None);
+    }
+
+    #[test]
+    fn best_ignores_dnfs() {
+        assert_eq!(best(&[None, Some(Duration::from_secs(40)), Some(Duration::from_secs(35))]), Some(Duration::from_secs(35)));
+        assert_eq!(best(&[None, None]), None);
+    }
+
+    #[test]
+    fn mean_moves_with_dnf_is_dnf() {
+        assert_eq!(mean_moves(&[Some(28), Some(31), Some(30)]), Some(29.666666666666668));
+        assert_eq!(mean_moves(&[Some(28), None, Some(30)]), None);
+        assert_eq!(mean_moves(&[]), None);
+    }
+
+    #[test]
+    fn rate_below_target() {
+        let results = [Some(Duration::from_secs(14)), Some(Duration::from_secs(15)), None, Some(Duration::from_secs(9))];
+        assert_eq!(rate_below(&results, Duration::from_secs(15)), Some(0.5));
+        assert_eq!(rate_below(&results, Duration::from_secs(5)), Some(0.0));
+        assert_eq!(rate_below(&[], Duration::from_secs(15)), None);
+    }
+
+    fn assert_near(value: f64, expected: f64) {
+        assert!((value - expected).abs() < 1e-6, "{} != {}", value, expected);
+    }
+
+    #[test]
+    fn linear_regression() {
+        let points = [(0.0, 20.0), (1.0, 19.0), (2.0, 18.0), (3.0, 17.0)];
+        let fit = regression(&points, Trend::Linear).unwrap();
+        assert_near(fit.intercept, 20.0);
+        assert_near(fit.slope, -1.0);
+        assert_near(fit.predict(5.0), 15.0);
+        assert_near(fit.reaches(15.0).unwrap(), 5.0);
+
+        let (low, high) = fit.band(5.0);
+        assert_near(low, 15.0);
+        assert_near(high, 15.0);
+    }
+
+    #[test]
+    fn power_law_regression() {
+        let points: Vec<_> = (0..6).map(|x| (x as f64, 30.0 * (x as f64 + 1.0).powf(-0.2))).collect();
+        let fit = regression(&points, Trend::PowerLaw).unwrap();
+        assert_near(fit.intercept, 30.0_f64.ln());
+        assert_near(fit.slope, -0.2);
+        assert_near(fit.predict(9.0), 30.0 * 10.0_f64.powf(-0.2));
+        assert_near(fit.reaches(30.0 * 10.0_f64.powf(-0.2)).unwrap(), 9.0);
+
+        assert_eq!(best_regression(&points).unwrap().trend, Trend::PowerLaw);
+        assert_eq!(best_regression(&[(0.0, 20.0), (1.0, 19.0), (2.0, 18.0)]).unwrap().trend, Trend::Linear);
+    }
+
+    #[test]
+    fn band_widens_away_from_data() {
+        let points = [(0.0, 20.0), (1.0, 19.5), (2.0, 17.5), (3.0, 17.0), (4.0, 16.0)];
+        let fit = regression(&points, Trend::Linear).unwrap();
+        let (low, high) = fit.band(2.0);
+        let (far_low, far_high) = fit.band(10.0);
+
+        assert!(low < fit.predict(2.0) && fit.predict(2.0) < high);
+        assert!(far_high - far_low > high - low);
+    }
+
+    #[test]
+    fn regression_needs_enough_points() {
+        assert!(regression(&[(0.0, 20.0), (1.0, 19.0)], Trend::Linear).is_none());
+        assert!(regression(&[(1.0, 20.0), (1.0, 19.0), (1.0, 18.0)], Trend::Linear).is_none());
+        assert!(regression(&[(0.0, 20.0), (1.0, -1.0), (2.0, 18.0)], Trend::PowerLaw).is_none());
+        assert!(best_regression(&[]).is_none());
+    }
+
+    #[test]
+    fn no_forecast_when_getting_slower() {
+        let fit = regression(&[(0.0, 15.0), (1.0, 16.0), (2.0, 17.0)], Trend::Linear).unwrap();
+        assert_eq!(fit.reaches(10.0), None);
+    }
+
+    #[test]
+    fn histogram_counts() {
+        let values = [10.0, 10.5, 11.2, 12.9, 13.0, 9.9];
+        assert_eq!(histogram(&values, 10.0, 1.0, 3), vec![2, 1, 1]);
+        assert_eq!(histogram(&[], 10.0, 1.0, 2), vec![0, 0]);
+    }
+
+    #[test]
+    fn t_distribution_probabilities() {
+        // With one degree of freedom, P(|T| > 1) is exactly 1/2, and with two
+        // P(|T| > t) = 1 - t / sqrt(2 + t^2).
+        assert_near(incomplete_beta(0.5, 0.5, 1.0 / 2.0), 0.5);
+        assert_near(incomplete_beta(1.0, 0.5, 2.0 / 6.0), 1.0 - 2.0 / 6.0_f64.sqrt());
+        assert_near(ln_gamma(5.0), 24.0_f64.ln());
+        assert_near(ln_gamma(0.5), PI.sqrt().ln());
+    }
+
+    #[test]
+    fn welch_test() {
+        let a = [27.5, 21.0, 19.0, 23.6, 17.0, 17.9, 16.9, 20.1, 21.9, 22.6, 23.1, 19.6, 19.0, 21.7, 21.4];
+        let b = [27.1, 22.0, 20.8, 23.4, 23.4, 23.5, 25.8, 22.0, 24.8, 20.2, 21.9, 22.1, 22.9, 20.5, 24.4];
+        let test = welch_t_test(&a, &b).unwrap();
+        assert_near(test.t, -2.455356398286006);
+        assert_near(test.degrees_of_freedom, 24.98852929023142);
+        assert!((test.p - 0.021).abs() < 0.001, "p = {}", test.p);
 
-        assert_eq!(std(&times), Some(Duration::from_millis(4463)));
+        assert!(welch_t_test(&a, &a).unwrap().p > 0.999);
+        assert!(welch_t_test(&a[..1], &b).is_none());
+        assert!(welch_t_test(&[1.0, 1.0], &[1.0, 1.0]).is_none());
     }
 }
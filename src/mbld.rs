@@ -0,0 +1,151 @@
+use serde::{Serialize, Deserialize};
+use std::{cmp::Reverse, time::Duration};
+use thiserror::Error;
+
+pub const MIN_CUBES: u32 = 2;
+pub const MAX_CUBES: u32 = 100;
+const TIME_PER_CUBE: Duration = Duration::from_secs(10 * 60);
+const MAX_TIME: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Error, Debug, PartialEq)]
+pub enum MultiBldError {
+    #[error("Expected a number of cubes, got {0:?}")]
+    InvalidCubeCount(String),
+    #[error("Multi-blind needs at least {MIN_CUBES} cubes")]
+    TooFewCubes,
+    #[error("Multi-blind allows at most {MAX_CUBES} cubes")]
+    TooManyCubes,
+    #[error("Expected solved/attempted, got {0:?}")]
+    InvalidResult(String),
+    #[error("Cannot solve more cubes than attempted")]
+    SolvedExceedsAttempted,
+    #[error("Expected {0} attempted cubes")]
+    AttemptedMismatch(u32)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiBldResult {
+    pub solved: u32,
+    pub attempted: u32
+}
+
+impl MultiBldResult {
+    pub fn missed(&self) -> u32 {
+        self.attempted - self.solved
+    }
+
+    pub fn points(&self) -> i64 {
+        self.solved as i64 - self.missed() as i64
+    }
+
+    // An attempt only counts with at least two cubes solved and no more
+    // cubes missed than solved.
+    pub fn counts(&self) -> bool {
+        self.solved >= MIN_CUBES && self.points() >= 0
+    }
+}
+
+pub fn time_limit(cubes: u32) -> Duration {
+    (TIME_PER_CUBE * cubes).min(MAX_TIME)
+}
+
+pub fn parse_cube_count(input: &str) -> Result<u32, MultiBldError> {
+    let cubes = input.trim().parse().map_err(|_| MultiBldError::InvalidCubeCount(input.to_string()))?;
+    if cubes < MIN_CUBES {
+        return Err(MultiBldError::TooFewCubes);
+    }
+    if cubes > MAX_CUBES {
+        return Err(MultiBldError::TooManyCubes);
+    }
+    Ok(cubes)
+}
+
+pub fn parse_result(input: &str, cubes: u32) -> Result<MultiBldResult, MultiBldError> {
+    let invalid = || MultiBldError::InvalidResult(input.to_string());
+
+    let (solved, attempted) = input.trim().split_once('/').ok_or_else(invalid)?;
+    let solved = solved.trim().parse().map_err(|_| invalid())?;
+    let attempted = attempted.trim().parse().map_err(|_| invalid())?;
+
+    if solved > attempted {
+        return Err(MultiBldError::SolvedExceedsAttempted);
+    }
+    if attempted != cubes {
+        return Err(MultiBldError::AttemptedMismatch(cubes));
+    }
+    Ok(MultiBldResult { solved, attempted })
+}
+
+// Results rank by more points, then less time, then fewer missed cubes.
+pub fn rank_key(result: &MultiBldResult, time: Duration) -> (Reverse<i64>, Duration, u32) {
+    (Reverse(result.points()), time, result.missed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(solved: u32, attempted: u32) -> MultiBldResult {
+        MultiBldResult { solved, attempted }
+    }
+
+    #[test]
+    fn points_and_missed() {
+        assert_eq!(result(7, 8).points(), 6);
+        assert_eq!(result(7, 8).missed(), 1);
+        assert_eq!(result(2, 5).points(), -1);
+    }
+
+    #[test]
+    fn attempts_that_count() {
+        assert!(result(2, 2).counts());
+        assert!(result(3, 6).counts());
+        assert!(!result(1, 2).counts());
+        assert!(!result(1, 1).counts());
+        assert!(!result(2, 5).counts());
+    }
+
+    #[test]
+    fn time_limit_is_ten_minutes_per_cube_up_to_an_hour() {
+        assert_eq!(time_limit(2), Duration::from_secs(20 * 60));
+        assert_eq!(time_limit(6), Duration::from_secs(60 * 60));
+        assert_eq!(time_limit(12), Duration::from_secs(60 * 60));
+    }
+
+    #[test]
+    fn parse_counts() {
+        assert_eq!(parse_cube_count(" 8 "), Ok(8));
+        assert_eq!(parse_cube_count("1"), Err(MultiBldError::TooFewCubes));
+        assert_eq!(parse_cube_count("100"), Ok(100));
+        assert_eq!(parse_cube_count("4000000000"), Err(MultiBldError::TooManyCubes));
+        assert!(matches!(parse_cube_count("eight"), Err(MultiBldError::InvalidCubeCount(_))));
+    }
+
+    #[test]
+    fn parse_results() {
+        assert_eq!(parse_result("7/8", 8), Ok(result(7, 8)));
+        assert_eq!(parse_result(" 3 / 4 ", 4), Ok(result(3, 4)));
+        assert_eq!(parse_result("5/4", 4), Err(MultiBldError::SolvedExceedsAttempted));
+        assert_eq!(parse_result("3/4", 5), Err(MultiBldError::AttemptedMismatch(5)));
+        assert!(matches!(parse_result("7-8", 8), Err(MultiBldError::InvalidResult(_))));
+    }
+
+    #[test]
+    fn ranking() {
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        let mut results = vec![
+            (result(5, 6), minutes(50)),
+            (result(9, 10), minutes(59)),
+            (result(4, 4), minutes(45)),
+            (result(5, 5), minutes(40)),
+        ];
+        results.sort_by_key(|(result, time)| rank_key(result, *time));
+
+        assert_eq!(results, vec![
+            (result(9, 10), minutes(59)),
+            (result(5, 5), minutes(40)),
+            (result(4, 4), minutes(45)),
+            (result(5, 6), minutes(50)),
+        ]);
+    }
+}
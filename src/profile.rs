@@ -0,0 +1,155 @@
+use crate::scramble::Puzzle;
+use crate::session::Session;
+use crate::wca::{self, Kind, Position, RankIndex, Records};
+
+// The WCA events the timer tracks, in the order the WCA lists them.
+const EVENTS: [Puzzle; 11] = [
+    Puzzle::Cube3x3,
+    Puzzle::Cube2x2,
+    Puzzle::Cube4x4,
+    Puzzle::Cube5x5,
+    Puzzle::Cube6x6,
+    Puzzle::Cube7x7,
+    Puzzle::Cube3x3Bld,
+    Puzzle::Cube3x3Fmc,
+    Puzzle::Cube4x4Bld,
+    Puzzle::Cube5x5Bld,
+    Puzzle::MultiBld
+];
+
+pub struct EventProfile {
+    pub puzzle: Puzzle,
+    // Best single and average over every session of the puzzle.
+    pub bests: [Option<u32>; 2],
+    pub kinch: Option<f64>,
+    pub ranks: [Option<Position>; 2]
+}
+
+pub struct Profile {
+    pub events: Vec<EventProfile>,
+    // Mean of the event Kinch scores, counting events without one as 0.
+    // None without any records to compare with.
+    pub kinch: Option<f64>,
+    // World ranks summed over events, an event without a result counting as
+    // one below the last ranked person. Multi-blind has no average.
+    pub sum_of_ranks: [Option<usize>; 2]
+}
+
+// Multi-blind is scored by points, plus the fraction of the hour left.
+fn multi_bld_score(result: u32) -> f64 {
+    let (points, seconds, _) = wca::decode_multi_bld(result);
+    points as f64 + (3600.0 - seconds as f64).max(0.0) / 3600.0
+}
+
+// A Kinch score is the percentage of the record a result reaches. Most events
+// are scored on the average, blindfolded and fewest moves events on whichever
+// of single and average scores higher, and multi-blind on the single.
+pub fn kinch_score(puzzle: Puzzle, bests: [Option<u32>; 2], records: &Records) -> Option<f64> {
+    let event = wca::event_id(puzzle)?;
+    let ratio = |kind: Kind| {
+        let (best, record) = (bests[kind as usize]?, records.get(event, kind)?);
+        Some(100.0 * record as f64 / best.max(1) as f64)
+    };
+
+    match puzzle {
+        Puzzle::MultiBld => {
+            let (best, record) = (bests[Kind::Single as usize]?, records.get(event, Kind::Single)?);
+            Some(100.0 * multi_bld_score(best) / multi_bld_score(record))
+        },
+        Puzzle::Cube3x3Bld | Puzzle::Cube4x4Bld | Puzzle::Cube5x5Bld | Puzzle::Cube3x3Fmc => {
+            [ratio(Kind::Single), ratio(Kind::Average)].into_iter().flatten().reduce(f64::max)
+        },
+        _ => ratio(Kind::Average),
+    }
+}
+
+fn combined_bests(sessions: &[Session], puzzle: Puzzle) -> [Option<u32>; 2] {
+    sessions.iter()
+        .filter(|session| session.puzzle() == puzzle)
+        .map(wca::personal_bests)
+        .fold([None, None], |bests, session| [0, 1].map(|i| match (bests[i], session[i]) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }))
+}
+
+pub fn profile(sessions: &[Session], records: &Records, index: Option<&RankIndex>) -> Profile {
+    let events: Vec<_> = EVENTS.iter()
+        .map(|&puzzle| {
+            let bests = combined_bests(sessions, puzzle);
+            let event = wca::event_id(puzzle).unwrap_or_default();
+            let rank = |kind: Kind| Some(index?.ranks(event, kind, bests[kind as usize]?, None)?.world);
+            EventProfile { puzzle, bests, kinch: kinch_score(puzzle, bests, records), ranks: [rank(Kind::Single), rank(Kind::Average)] }
+        })
+        .collect();
+
+    let kinch = (records != &Records::default())
+        .then(|| events.iter().filter_map(|event| event.kinch).sum::<f64>() / events.len() as f64);
+
+    let sum_of_ranks = [Kind::Single, Kind::Average].map(|kind| {
+        let index = index?;
+        events.iter()
+            .filter(|event| kind == Kind::Single || event.puzzle != Puzzle::MultiBld)
+            .map(|event| {
+                let event_id = wca::event_id(event.puzzle).unwrap_or_default();
+                let result = event.bests[kind as usize].unwrap_or(u32::MAX);
+                index.ranks(event_id, kind, result, None).map_or(1, |ranks| ranks.world.rank)
+            })
+            .sum::<usize>()
+            .into()
+    });
+
+    Profile { events, kinch, sum_of_ranks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mbld::MultiBldResult;
+    use crate::solve::Solve;
+    use std::time::Duration;
+
+    fn records() -> Records {
+        let mut records = Records::default();
+        for (event, kind, result) in [
+            ("333", Kind::Single, 300), ("333", Kind::Average, 400),
+            ("333bf", Kind::Single, 1200), ("333bf", Kind::Average, 1500),
+            ("333mbf", Kind::Single, wca::encode_multi_bld(&MultiBldResult { solved: 60, attempted: 60 }, Duration::from_secs(3600))),
+        ] {
+            records.set(event, kind, result);
+        }
+        records
+    }
+
+    #[test]
+    fn kinch_scores() {
+        let records = records();
+        assert_eq!(kinch_score(Puzzle::Cube3x3, [Some(600), Some(800)], &records), Some(50.0));
+        assert_eq!(kinch_score(Puzzle::Cube3x3, [Some(600), None], &records), None);
+        // The better of single and average counts for blindfolded events.
+        assert_eq!(kinch_score(Puzzle::Cube3x3Bld, [Some(2400), Some(6000)], &records), Some(50.0));
+        assert_eq!(kinch_score(Puzzle::Cube3x3Bld, [None, Some(3000)], &records), Some(50.0));
+        assert_eq!(kinch_score(Puzzle::Cube4x4, [Some(2000), Some(2500)], &records), None);
+        assert_eq!(kinch_score(Puzzle::Relay2To4, [Some(2000), Some(2500)], &records), None);
+
+        let half = wca::encode_multi_bld(&MultiBldResult { solved: 30, attempted: 30 }, Duration::from_secs(1800));
+        let score = kinch_score(Puzzle::MultiBld, [Some(half), None], &records).unwrap();
+        assert!((score - 100.0 * 30.5 / 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn profile_over_sessions() {
+        let times = |secs: &[f64]| Session::from_solves(secs.iter()
+            .map(|&s| Solve::build("R".to_owned(), Some(Duration::from_secs_f64(s)), None).unwrap())
+            .collect());
+        let sessions = [times(&[8.0, 8.0, 8.0, 8.0, 8.0]), times(&[6.0])];
+
+        let profile = profile(&sessions, &records(), None);
+        assert_eq!(profile.events.len(), EVENTS.len());
+        assert_eq!(profile.events[0].bests, [Some(600), Some(800)]);
+        assert_eq!(profile.kinch, Some(50.0 / EVENTS.len() as f64));
+        assert_eq!(profile.sum_of_ranks, [None, None]);
+
+        assert_eq!(super::profile(&sessions, &Records::default(), None).kinch, None);
+    }
+}
@@ -0,0 +1,243 @@
+use crate::scramble::Puzzle;
+use crate::solve::{Penalty, Solve};
+use crate::ui::parse_time;
+
+use std::cmp::Ordering;
+use std::time::Duration;
+use chrono::{NaiveDate, TimeZone};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum QueryError {
+    #[error("Expected field, operator and value in {0:?}")]
+    InvalidTerm(String),
+    #[error("Unknown field: {0}")]
+    UnknownField(String),
+    #[error("{0} can only be matched with `:`")]
+    InvalidOperator(String),
+    #[error("Invalid value for {0}: {1}")]
+    InvalidValue(String, String)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Equal,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual
+}
+
+impl Operator {
+    // Longer operators first so `<=` is not read as `<`.
+    const SYMBOLS: [(&'static str, Operator); 6] = [
+        ("<=", Operator::LessOrEqual),
+        (">=", Operator::GreaterOrEqual),
+        (":", Operator::Equal),
+        ("=", Operator::Equal),
+        ("<", Operator::Less),
+        (">", Operator::Greater),
+    ];
+
+    fn accepts(&self, ordering: Ordering) -> bool {
+        match self {
+            Operator::Equal => ordering == Ordering::Equal,
+            Operator::Less => ordering == Ordering::Less,
+            Operator::LessOrEqual => ordering != Ordering::Greater,
+            Operator::Greater => ordering == Ordering::Greater,
+            Operator::GreaterOrEqual => ordering != Ordering::Less,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    Puzzle(Puzzle),
+    Tag(String),
+    Time(Operator, Duration),
+    Moves(Operator, u32),
+    Date(Operator, NaiveDate),
+    Penalty(Option<Penalty>)
+}
+
+// A filter over solves, written as space separated terms that must all
+// match, e.g. `puzzle:4x4 tag:oh time<20 date>=2026-01-01 penalty:none`.
+// Fewest moves results compare with `moves<30` instead of a time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    source: String,
+    terms: Vec<Term>
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Query, QueryError> {
+        let terms = input.split_whitespace().map(parse_term).collect::<Result<_, _>>()?;
+        Ok(Query { source: input.trim().to_string(), terms })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    // Queries naming a puzzle look across every session.
+    pub fn puzzle(&self) -> Option<Puzzle> {
+        self.terms.iter().find_map(|term| match term {
+            Term::Puzzle(puzzle) => Some(*puzzle),
+            _ => None,
+        })
+    }
+
+    // Dates are compared as days in the given time zone. Only timed results
+    // match time comparisons and only fewest moves results match move
+    // comparisons, neither matching DNFs. Undated solves never match date
+    // comparisons.
+    pub fn matches<Tz: TimeZone>(&self, puzzle: Puzzle, solve: &Solve, zone: &Tz) -> bool {
+        self.terms.iter().all(|term| match term {
+            Term::Puzzle(p) => *p == puzzle,
+            Term::Tag(tag) => solve.tags().iter().any(|t| t.eq_ignore_ascii_case(tag)),
+            Term::Time(operator, time) => solve.result().is_some_and(|result| operator.accepts(result.cmp(time))),
+            Term::Moves(operator, moves) => solve.moves().is_some_and(|count| operator.accepts(count.cmp(moves))),
+            Term::Date(operator, day) => solve.date()
+                .is_some_and(|date| operator.accepts(date.with_timezone(zone).date_naive().cmp(day))),
+            Term::Penalty(penalty) => penalty == solve.penalty(),
+        })
+    }
+}
+
+fn parse_term(term: &str) -> Result<Term, QueryError> {
+    let (field, operator, value) = term.find(|c| ":=<>".contains(c))
+        .and_then(|start| {
+            let (symbol, operator) = Operator::SYMBOLS.iter().find(|(symbol, _)| term[start..].starts_with(symbol))?;
+            Some((&term[..start], *operator, &term[start + symbol.len()..]))
+        })
+        .filter(|(field, _, value)| !field.is_empty() && !value.is_empty())
+        .ok_or_else(|| QueryError::InvalidTerm(term.to_string()))?;
+
+    let field = field.to_lowercase();
+    let invalid_value = || QueryError::InvalidValue(field.clone(), value.to_string());
+    let equality_only = |term: Term| match operator {
+        Operator::Equal => Ok(term),
+        _ => Err(QueryError::InvalidOperator(field.clone())),
+    };
+
+    match field.as_str() {
+        "puzzle" => equality_only(Term::Puzzle(
+            Puzzle::from_name(value).ok_or_else(invalid_value)?
+        )),
+        "tag" => equality_only(Term::Tag(value.to_string())),
+        "time" => Ok(Term::Time(operator, parse_time(value).map_err(|_| invalid_value())?)),
+        "moves" => Ok(Term::Moves(operator, value.parse().map_err(|_| invalid_value())?)),
+        "date" => Ok(Term::Date(operator, value.parse().map_err(|_| invalid_value())?)),
+        "penalty" => equality_only(Term::Penalty(match value.to_lowercase().as_str() {
+            "none" => None,
+            "+2" | "plus2" => Some(Penalty::Plus2),
+            "dnf" => Some(Penalty::DNF),
+            _ => return Err(invalid_value()),
+        })),
+        _ => Err(QueryError::UnknownField(field.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+
+    fn solve(millis: Option<u64>, penalty: Option<Penalty>, date: &str, tags: &[&str]) -> Solve {
+        let mut solve = Solve::build("R".to_owned(), millis.map(Duration::from_millis), penalty).unwrap();
+        solve.set_date(date.parse::<DateTime<Utc>>().unwrap());
+        solve.set_tags(tags.iter().map(|t| t.to_string()).collect());
+        solve
+    }
+
+    fn matches(query: &str, puzzle: Puzzle, solve: &Solve) -> bool {
+        Query::parse(query).unwrap().matches(puzzle, solve, &Utc)
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        let solve = solve(None, Some(Penalty::DNF), "2026-01-01T12:00:00Z", &[]);
+        assert!(matches("", Puzzle::Cube3x3, &solve));
+        assert_eq!(Query::parse("  ").unwrap().puzzle(), None);
+    }
+
+    #[test]
+    fn full_query() {
+        let query = "puzzle:4x4 tag:oh time<20 date>=2026-01-01 penalty:none";
+        let oh = solve(Some(18500), None, "2026-01-05T12:00:00Z", &["OH"]);
+        assert_eq!(Query::parse(query).unwrap().puzzle(), Some(Puzzle::Cube4x4));
+        assert!(matches(query, Puzzle::Cube4x4, &oh));
+        assert!(!matches(query, Puzzle::Cube3x3, &oh));
+
+        assert!(!matches(query, Puzzle::Cube4x4, &solve(Some(18500), None, "2026-01-05T12:00:00Z", &[])));
+        assert!(!matches(query, Puzzle::Cube4x4, &solve(Some(20000), None, "2026-01-05T12:00:00Z", &["oh"])));
+        assert!(!matches(query, Puzzle::Cube4x4, &solve(Some(18500), None, "2025-12-31T12:00:00Z", &["oh"])));
+        assert!(!matches(query, Puzzle::Cube4x4, &solve(Some(16500), Some(Penalty::Plus2), "2026-01-05T12:00:00Z", &["oh"])));
+    }
+
+    #[test]
+    fn time_comparisons() {
+        let timed = solve(Some(12340), None, "2026-01-01T12:00:00Z", &[]);
+        assert!(matches("time<=12.34", Puzzle::Cube3x3, &timed));
+        assert!(matches("time>=12.34", Puzzle::Cube3x3, &timed));
+        assert!(matches("time=12.34", Puzzle::Cube3x3, &timed));
+        assert!(matches("time>10", Puzzle::Cube3x3, &timed));
+        assert!(!matches("time>1:00", Puzzle::Cube3x3, &timed));
+
+        let dnf = solve(None, Some(Penalty::DNF), "2026-01-01T12:00:00Z", &[]);
+        assert!(!matches("time>0", Puzzle::Cube3x3, &dnf));
+        assert!(matches("penalty:DNF", Puzzle::Cube3x3, &dnf));
+    }
+
+    #[test]
+    fn move_comparisons() {
+        let fmc = Solve::build_fmc("R".to_owned(), "R'".to_owned(), Some(28));
+        assert!(matches("moves<30", Puzzle::Cube3x3Fmc, &fmc));
+        assert!(!matches("moves>=29", Puzzle::Cube3x3Fmc, &fmc));
+        assert!(!matches("time>0", Puzzle::Cube3x3Fmc, &fmc));
+        assert!(!matches("penalty:dnf", Puzzle::Cube3x3Fmc, &fmc));
+        assert!(!matches("moves<30", Puzzle::Cube3x3Fmc, &Solve::build_fmc("R".to_owned(), String::new(), None)));
+        assert!(!matches("moves<30", Puzzle::Cube3x3, &solve(Some(12000), None, "2026-01-01T12:00:00Z", &[])));
+        assert!(matches!(Query::parse("moves<few"), Err(QueryError::InvalidValue(..))));
+    }
+
+    #[test]
+    fn plus2_counts_in_time() {
+        let solve = solve(Some(12000), Some(Penalty::Plus2), "2026-01-01T12:00:00Z", &[]);
+        assert!(matches("time>13 penalty:+2", Puzzle::Cube3x3, &solve));
+    }
+
+    #[test]
+    fn date_comparisons() {
+        let solve = solve(Some(12000), None, "2026-03-10T23:30:00Z", &[]);
+        assert!(matches("date:2026-03-10", Puzzle::Cube3x3, &solve));
+        assert!(matches("date<2026-03-11", Puzzle::Cube3x3, &solve));
+        assert!(!matches("date>2026-03-10", Puzzle::Cube3x3, &solve));
+
+        let zone = chrono::FixedOffset::east_opt(3600).unwrap();
+        assert!(Query::parse("date:2026-03-11").unwrap().matches(Puzzle::Cube3x3, &solve, &zone));
+
+        let undated = Solve::build("R".to_owned(), Some(Duration::from_secs(10)), None).unwrap();
+        assert!(!matches("date<2030-01-01", Puzzle::Cube3x3, &undated));
+    }
+
+    #[test]
+    fn puzzle_names() {
+        let solve = solve(Some(12000), None, "2026-01-01T12:00:00Z", &[]);
+        assert!(matches("puzzle:3bld", Puzzle::Cube3x3Bld, &solve));
+        assert!(matches("puzzle=2-4-relay", Puzzle::Relay2To4, &solve));
+    }
+
+    #[test]
+    fn invalid_queries() {
+        assert_eq!(Query::parse("oh"), Err(QueryError::InvalidTerm("oh".to_string())));
+        assert_eq!(Query::parse("tag:"), Err(QueryError::InvalidTerm("tag:".to_string())));
+        assert_eq!(Query::parse(":oh"), Err(QueryError::InvalidTerm(":oh".to_string())));
+        assert_eq!(Query::parse("colour:red"), Err(QueryError::UnknownField("colour".to_string())));
+        assert!(matches!(Query::parse("puzzle:8x8"), Err(QueryError::InvalidValue(..))));
+        assert!(matches!(Query::parse("time<fast"), Err(QueryError::InvalidValue(..))));
+        assert!(matches!(Query::parse("date>=yesterday"), Err(QueryError::InvalidValue(..))));
+        assert!(matches!(Query::parse("penalty:maybe"), Err(QueryError::InvalidValue(..))));
+        assert_eq!(Query::parse("tag<oh"), Err(QueryError::InvalidOperator("tag".to_string())));
+    }
+}
@@ -0,0 +1,284 @@
+use crate::math::{self, Regression};
+use crate::scramble::Puzzle;
+use crate::solve::{Penalty, Solve};
+use crate::ui::format_time;
+
+use std::time::Duration;
+use chrono::{Days, NaiveDate, TimeZone};
+
+#[derive(Debug, PartialEq)]
+pub struct DaySummary {
+    // None for solves recorded before solves were dated.
+    pub day: Option<NaiveDate>,
+    pub count: usize,
+    pub mean: Option<Duration>,
+    pub best: Option<Duration>,
+    pub best_ao5: Option<Duration>,
+    pub best_ao12: Option<Duration>,
+    pub practice: Duration
+}
+
+// Best trimmed average of `n` consecutive results.
+pub fn best_average(results: &[Option<Duration>], n: usize) -> Option<Duration> {
+    results.windows(n).filter_map(math::trimmed_avg).min()
+}
+
+// Groups solves by the day they were recorded on in the given time zone,
+// undated solves first. The mean leaves out DNFs, and practice time is the
+// sum of the recorded times, DNFs included. Fewest moves results have no
+// time and are left out.
+pub fn daily_summaries<'a, Tz: TimeZone>(solves: impl IntoIterator<Item = &'a Solve>, zone: &Tz) -> Vec<DaySummary> {
+    let mut days: Vec<(Option<NaiveDate>, Vec<&Solve>)> = Vec::new();
+    for solve in solves.into_iter().filter(|solve| !solve.is_fewest_moves()) {
+        let day = solve.date().map(|date| date.with_timezone(zone).date_naive());
+        match days.iter_mut().find(|(d, _)| *d == day) {
+            Some((_, solves)) => solves.push(solve),
+            None => days.push((day, vec![solve])),
+        }
+    }
+    days.sort_by_key(|(day, _)| *day);
+
+    days.into_iter()
+        .map(|(day, solves)| {
+            let results: Vec<_> = solves.iter().map(|solve| solve.result()).collect();
+            let times: Vec<_> = results.iter().flatten().copied().collect();
+            DaySummary {
+                day,
+                count: solves.len(),
+                mean: math::avg(&times),
+                best: math::best(&results),
+                best_ao5: best_average(&results, 5),
+                best_ao12: best_average(&results, 12),
+                practice: solves.iter().filter_map(|solve| *solve.time()).sum(),
+            }
+        })
+        .collect()
+}
+
+pub struct Forecast {
+    pub first_day: NaiveDate,
+    // Days since the first day against that day's mean in seconds.
+    pub points: Vec<(f64, f64)>,
+    pub regression: Regression,
+    // When the trend reaches the goal, if there is one and it is improving.
+    pub date: Option<NaiveDate>
+}
+
+// Fits a trend to the mean of each dated day.
+pub fn forecast(summaries: &[DaySummary], goal: Option<Duration>) -> Option<Forecast> {
+    let days: Vec<_> = summaries.iter()
+        .filter_map(|summary| Some((summary.day?, summary.mean?)))
+        .collect();
+    let first_day = days.first()?.0;
+    let points: Vec<_> = days.iter()
+        .map(|(day, mean)| ((*day - first_day).num_days() as f64, mean.as_secs_f64()))
+        .collect();
+
+    let regression = math::best_regression(&points)?;
+    let date = goal
+        .and_then(|goal| regression.reaches(goal.as_secs_f64()))
+        .and_then(|x| first_day.checked_add_days(Days::new(x.max(0.0).ceil() as u64)));
+
+    Some(Forecast { first_day, points, regression, date })
+}
+
+const COLUMNS: [&str; 7] = ["Day", "Solves", "Mean", "Best", "Best ao5", "Best ao12", "Practice"];
+
+pub fn rows(summaries: &[DaySummary]) -> Vec<[String; 7]> {
+    let format = |time: Option<Duration>| time.map(format_time).unwrap_or_else(|| "-".to_string());
+    summaries.iter()
+        .map(|summary| [
+            summary.day.map(|day| day.to_string()).unwrap_or_else(|| "undated".to_string()),
+            summary.count.to_string(),
+            format(summary.mean),
+            format(summary.best),
+            format(summary.best_ao5),
+            format(summary.best_ao12),
+            format_time(summary.practice),
+        ])
+        .collect()
+}
+
+pub fn header() -> [String; 7] {
+    COLUMNS.map(String::from)
+}
+
+pub fn to_text(puzzle: Puzzle, summaries: &[DaySummary]) -> String {
+    let mut table = vec![header()];
+    table.extend(rows(summaries));
+
+    let widths: Vec<_> = (0..COLUMNS.len())
+        .map(|column| table.iter().map(|row| row[column].chars().count()).max().unwrap_or(0))
+        .collect();
+
+    let mut text = format!("{} sessions by day\n\n", puzzle.name());
+    for row in table {
+        let cells: Vec<_> = row.iter().zip(&widths).map(|(cell, &width)| format!("{:<width$}", cell)).collect();
+        text.push_str(cells.join("  ").trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+pub fn to_markdown(puzzle: Puzzle, summaries: &[DaySummary]) -> String {
+    let mut markdown = format!("# {} sessions by day\n\n", puzzle.name());
+    markdown.push_str(&format!("| {} |\n", COLUMNS.join(" | ")));
+    markdown.push_str(&format!("|{}\n", "---|".repeat(COLUMNS.len())));
+    for row in rows(summaries) {
+        markdown.push_str(&format!("| {} |\n", row.join(" | ")));
+    }
+    markdown
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// One row per solve, with the time as recorded and the result after any
+// penalty.
+pub fn to_csv<'a>(solves: impl IntoIterator<Item = &'a Solve>) -> String {
+    let mut csv = "date,time,penalty,result,scramble,tags\n".to_string();
+    for solve in solves {
+        let fields = [
+            solve.date().map(|date| date.to_rfc3339()).unwrap_or_default(),
+            solve.time().map(format_time).unwrap_or_default(),
+            match solve.penalty() {
+                Some(Penalty::Plus2) => "+2".to_string(),
+                Some(Penalty::DNF) => "DNF".to_string(),
+                None => String::new(),
+            },
+            solve.result().map(format_time).unwrap_or_else(|| "DNF".to_string()),
+            solve.scramble().to_string(),
+            solve.tags().join(" "),
+        ];
+        let fields: Vec<_> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve::Penalty;
+    use chrono::{DateTime, Utc};
+
+    fn solve_on(date: &str, millis: Option<u64>) -> Solve {
+        let penalty = if millis.is_none() { Some(Penalty::DNF) } else { None };
+        let mut solve = Solve::build("R U".to_owned(), millis.map(Duration::from_millis), penalty).unwrap();
+        solve.set_date(date.parse::<DateTime<Utc>>().unwrap());
+        solve
+    }
+
+    #[test]
+    fn best_average_over_windows() {
+        let results: Vec<_> = [10, 20, 12, 11, 13, 14, 9].iter().map(|&s| Some(Duration::from_secs(s))).collect();
+        assert_eq!(best_average(&results, 5), Some(Duration::from_secs(12)));
+        assert_eq!(best_average(&results[..4], 5), None);
+    }
+
+    #[test]
+    fn groups_solves_by_day() {
+        let mut solves = vec![Solve::build("R".to_owned(), Some(Duration::from_secs(30)), None).unwrap()];
+        solves.extend(["2026-03-02T08:00:00Z", "2026-03-02T23:30:00Z", "2026-03-01T12:00:00Z"]
+            .iter()
+            .zip([Some(12000), None, Some(15500)])
+            .map(|(date, millis)| solve_on(date, millis)));
+
+        let summaries = daily_summaries(&solves, &Utc);
+        assert_eq!(summaries.len(), 3);
+        assert_eq!(summaries[0].day, None);
+        assert_eq!(summaries[1].day, NaiveDate::from_ymd_opt(2026, 3, 1));
+        assert_eq!(summaries[2], DaySummary {
+            day: NaiveDate::from_ymd_opt(2026, 3, 2),
+            count: 2,
+            mean: Some(Duration::from_secs(12)),
+            best: Some(Duration::from_secs(12)),
+            best_ao5: None,
+            best_ao12: None,
+            practice: Duration::from_secs(12),
+        });
+    }
+
+    #[test]
+    fn dnf_time_counts_as_practice() {
+        let mut dnf = solve_on("2026-03-01T12:00:00Z", Some(20000));
+        dnf.set_penalty(Some(Penalty::DNF)).unwrap();
+        let solves = [solve_on("2026-03-01T13:00:00Z", Some(10000)), dnf];
+
+        let summaries = daily_summaries(&solves, &Utc);
+        assert_eq!(summaries[0].mean, Some(Duration::from_secs(10)));
+        assert_eq!(summaries[0].practice, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn fewest_moves_have_no_day() {
+        let solves = [Solve::build_fmc("R".to_owned(), "R'".to_owned(), Some(1))];
+        assert!(daily_summaries(&solves, &Utc).is_empty());
+    }
+
+    #[test]
+    fn days_follow_the_time_zone() {
+        let solves = [solve_on("2026-03-02T08:00:00Z", Some(12000)), solve_on("2026-03-02T23:30:00Z", Some(13000))];
+        let zone = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+        let days: Vec<_> = daily_summaries(&solves, &zone).iter().map(|summary| summary.day.unwrap().to_string()).collect();
+        assert_eq!(days, ["2026-03-02", "2026-03-03"]);
+    }
+
+    #[test]
+    fn exports() {
+        let solves: Vec<_> = (0..5).map(|i| solve_on("2026-03-01T12:00:00Z", Some(10000 + i * 1000))).collect();
+        let summaries = daily_summaries(&solves, &Utc);
+
+        assert_eq!(to_markdown(Puzzle::Cube3x3, &summaries), "\
+# 3x3 sessions by day
+
+| Day | Solves | Mean | Best | Best ao5 | Best ao12 | Practice |
+|---|---|---|---|---|---|---|
+| 2026-03-01 | 5 | 12.00 | 10.00 | 12.00 | - | 1:00.00 |
+");
+        assert_eq!(to_text(Puzzle::Cube3x3, &summaries), "\
+3x3 sessions by day
+
+Day         Solves  Mean   Best   Best ao5  Best ao12  Practice
+2026-03-01  5       12.00  10.00  12.00     -          1:00.00
+");
+    }
+
+    #[test]
+    fn csv_export() {
+        let mut tagged = solve_on("2026-03-01T12:00:00Z", Some(10000));
+        tagged.set_tags(vec!["oh".to_string(), "home".to_string()]);
+        let mut plus_two = Solve::build("R U\nF, \"B\"".to_owned(), Some(Duration::from_millis(61500)), Some(Penalty::Plus2)).unwrap();
+        plus_two.set_date("2026-03-02T08:00:00Z".parse::<DateTime<Utc>>().unwrap());
+
+        assert_eq!(to_csv(&[tagged, plus_two, solve_on("2026-03-03T12:00:00Z", None)]), "\
+date,time,penalty,result,scramble,tags
+2026-03-01T12:00:00+00:00,10.00,,10.00,R U,oh home
+2026-03-02T08:00:00+00:00,1:01.50,+2,1:03.50,\"R U
+F, \"\"B\"\"\",
+2026-03-03T12:00:00+00:00,,DNF,DNF,R U,
+");
+    }
+
+    #[test]
+    fn forecasts_goal_date() {
+        let solves: Vec<_> = [("2026-03-01", 20000), ("2026-03-02", 19000), ("2026-03-04", 17000)]
+            .iter()
+            .map(|(day, millis)| solve_on(&format!("{}T12:00:00Z", day), Some(*millis)))
+            .collect();
+        let summaries = daily_summaries(&solves, &Utc);
+
+        let forecast = forecast(&summaries, Some(Duration::from_secs(15))).unwrap();
+        assert_eq!(forecast.points, vec![(0.0, 20.0), (1.0, 19.0), (3.0, 17.0)]);
+        assert_eq!(forecast.date, NaiveDate::from_ymd_opt(2026, 3, 6));
+
+        assert_eq!(super::forecast(&summaries, None).unwrap().date, None);
+        assert!(super::forecast(&summaries[..2], Some(Duration::from_secs(15))).is_none());
+    }
+}
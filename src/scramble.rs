@@ -1,24 +1,276 @@
+use crate::cube::{format_alg, Cube, Face, Layers, Move};
+use crate::fmc;
+use crate::mbld;
+use crate::solver::{self, Difficulty};
+
 use rand::Rng;
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ScrambleError {
+    #[error("No scramble met the filter in {0} attempts")]
+    FilterNotMet(usize)
+}
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum Puzzle {
-    Cube3x3
+    Cube2x2,
+    #[default]
+    Cube3x3,
+    Cube4x4,
+    Cube5x5,
+    Cube6x6,
+    Cube7x7,
+    Cube3x3Bld,
+    Cube4x4Bld,
+    Cube5x5Bld,
+    Cube3x3Fmc,
+    Relay2To4,
+    Relay2To7,
+    MultiBld
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    AverageOf5,
+    BestOf3
+}
+
+impl Puzzle {
+    pub const ALL: [Puzzle; 13] = [
+        Puzzle::Cube2x2,
+        Puzzle::Cube3x3,
+        Puzzle::Cube4x4,
+        Puzzle::Cube5x5,
+        Puzzle::Cube6x6,
+        Puzzle::Cube7x7,
+        Puzzle::Cube3x3Bld,
+        Puzzle::Cube4x4Bld,
+        Puzzle::Cube5x5Bld,
+        Puzzle::Cube3x3Fmc,
+        Puzzle::Relay2To4,
+        Puzzle::Relay2To7,
+        Puzzle::MultiBld
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Puzzle::Cube2x2 => "2x2",
+            Puzzle::Cube3x3 => "3x3",
+            Puzzle::Cube4x4 => "4x4",
+            Puzzle::Cube5x5 => "5x5",
+            Puzzle::Cube6x6 => "6x6",
+            Puzzle::Cube7x7 => "7x7",
+            Puzzle::Cube3x3Bld => "3BLD",
+            Puzzle::Cube4x4Bld => "4BLD",
+            Puzzle::Cube5x5Bld => "5BLD",
+            Puzzle::Cube3x3Fmc => "FMC",
+            Puzzle::Relay2To4 => "2-4 relay",
+            Puzzle::Relay2To7 => "2-7 relay",
+            Puzzle::MultiBld => "MBLD",
+        }
+    }
+
+    // Names as shown, matched case-insensitively with `-` for spaces, so
+    // `2-4-relay` finds the 2-4 relay.
+    pub fn from_name(name: &str) -> Option<Puzzle> {
+        Puzzle::ALL.into_iter().find(|p| p.name().replace(' ', "-").eq_ignore_ascii_case(name))
+    }
+
+    pub fn size(&self) -> Option<usize> {
+        match self {
+            Puzzle::Cube2x2 => Some(2),
+            Puzzle::Cube3x3 | Puzzle::Cube3x3Bld | Puzzle::Cube3x3Fmc => Some(3),
+            Puzzle::Cube4x4 | Puzzle::Cube4x4Bld => Some(4),
+            Puzzle::Cube5x5 | Puzzle::Cube5x5Bld => Some(5),
+            Puzzle::Cube6x6 => Some(6),
+            Puzzle::Cube7x7 => Some(7),
+            Puzzle::Relay2To4 | Puzzle::Relay2To7 | Puzzle::MultiBld => None,
+        }
+    }
+
+    // Puzzles solved back to back, in order, under a single time.
+    pub fn relay(&self) -> Option<&'static [Puzzle]> {
+        match self {
+            Puzzle::Relay2To4 => Some(&[Puzzle::Cube2x2, Puzzle::Cube3x3, Puzzle::Cube4x4]),
+            Puzzle::Relay2To7 => Some(&[
+                Puzzle::Cube2x2,
+                Puzzle::Cube3x3,
+                Puzzle::Cube4x4,
+                Puzzle::Cube5x5,
+                Puzzle::Cube6x6,
+                Puzzle::Cube7x7
+            ]),
+            _ => None
+        }
+    }
+
+    // Blindfolded times include inspection, so those events are ranked by
+    // best single and reported as a mean of 3 rather than an average of 5.
+    // Fewest moves attempts are an hour long and follow the same format.
+    pub fn format(&self) -> Format {
+        match self {
+            Puzzle::Cube3x3Bld | Puzzle::Cube4x4Bld | Puzzle::Cube5x5Bld | Puzzle::Cube3x3Fmc | Puzzle::MultiBld => Format::BestOf3,
+            _ => Format::AverageOf5,
+        }
+    }
+
+    // Fewest moves and multi-blind results are more than a single time.
+    pub fn is_timed(&self) -> bool {
+        !matches!(self, Puzzle::Cube3x3Fmc | Puzzle::MultiBld)
+    }
+
+    // Inspection is part of a blindfolded attempt's time.
+    pub fn is_blindfolded(&self) -> bool {
+        matches!(self, Puzzle::Cube3x3Bld | Puzzle::Cube4x4Bld | Puzzle::Cube5x5Bld | Puzzle::MultiBld)
+    }
+
+    pub fn next(&self) -> Puzzle {
+        let index = Puzzle::ALL.iter().position(|p| p == self).unwrap();
+        Puzzle::ALL[(index + 1) % Puzzle::ALL.len()]
+    }
+}
+
+const MAX_FILTER_ATTEMPTS: usize = 10_000;
+
+// Constraints used to reject easy 3x3 scrambles. The cross is measured on
+// `cross_colour`, or on whichever colour is shortest when it is `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrambleFilter {
+    pub cross_colour: Option<Face>,
+    pub min_cross_length: usize,
+    pub reject_built_pairs: bool,
+    pub reject_solved_pairs: bool
+}
+
+impl Default for ScrambleFilter {
+    fn default() -> Self {
+        ScrambleFilter {
+            cross_colour: Some(Face::U),
+            min_cross_length: 0,
+            reject_built_pairs: false,
+            reject_solved_pairs: false
+        }
+    }
+}
+
+impl ScrambleFilter {
+    pub fn hard() -> Self {
+        ScrambleFilter {
+            min_cross_length: 6,
+            reject_built_pairs: true,
+            reject_solved_pairs: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.min_cross_length > 0 || self.reject_built_pairs || self.reject_solved_pairs
+    }
+
+    // The cross length of a 3x3 scramble on the filter's cross colour.
+    pub fn cross_length(&self, p: Puzzle, scramble: &str) -> Option<usize> {
+        if p.size() != Some(3) {
+            return None;
+        }
+        let mut cube = Cube::new(3);
+        cube.apply_alg(scramble).ok()?;
+        Some(match self.cross_colour {
+            Some(colour) => solver::cross(&cube, colour).len(),
+            None => Face::ALL.iter().map(|&colour| solver::cross(&cube, colour).len()).min().unwrap(),
+        })
+    }
+
+    fn difficulty(&self, cube: &Cube) -> Difficulty {
+        match self.cross_colour {
+            Some(colour) => solver::difficulty(cube, colour),
+            None => Face::ALL.iter()
+                .map(|&colour| solver::difficulty(cube, colour))
+                .min_by_key(|difficulty| difficulty.cross_length)
+                .unwrap(),
+        }
+    }
+
+    fn accepts(&self, difficulty: &Difficulty) -> bool {
+        difficulty.cross_length >= self.min_cross_length
+            && !(self.reject_built_pairs && difficulty.built_pairs > 0)
+            && !(self.reject_solved_pairs && difficulty.solved_pairs > 0)
+    }
 }
 
 pub struct Scrambler {}
 impl Scrambler {
+    // Regenerates 3x3 scrambles until the filter accepts one, giving up after
+    // a bounded number of attempts. Returns the scramble with its cross
+    // length, which every 3x3 scramble has worked out; the F2L pairs are only
+    // looked at while the filter is active.
+    pub fn new_filtered_scramble(p: Puzzle, filter: &ScrambleFilter, rng: &mut impl Rng) -> Result<(String, Option<usize>), ScrambleError> {
+        Self::filtered_scramble(p, filter, rng, MAX_FILTER_ATTEMPTS)
+    }
+
+    fn filtered_scramble(p: Puzzle, filter: &ScrambleFilter, rng: &mut impl Rng, attempts: usize) -> Result<(String, Option<usize>), ScrambleError> {
+        if p.size() != Some(3) || !filter.is_active() {
+            let scramble = Self::new_scramble_with(p, rng);
+            let cross_length = filter.cross_length(p, &scramble);
+            return Ok((scramble, cross_length));
+        }
+
+        for _ in 0..attempts {
+            let scramble = Self::new_scramble_with(p, rng);
+            let mut cube = Cube::new(3);
+            cube.apply_alg(&scramble).unwrap();
+
+            let difficulty = filter.difficulty(&cube);
+            if filter.accepts(&difficulty) {
+                return Ok((scramble, Some(difficulty.cross_length)));
+            }
+        }
+        Err(ScrambleError::FilterNotMet(attempts))
+    }
+
     pub fn new_scramble(p: Puzzle) -> String {
+        Self::new_scramble_with(p, &mut rand::thread_rng())
+    }
+
+    // Relay scrambles hold one line per puzzle.
+    fn new_scramble_with(p: Puzzle, rng: &mut impl Rng) -> String {
         match p {
-            Puzzle::Cube3x3 => Self::scramble_3x3()
+            Puzzle::Cube2x2 => Self::random_face_moves(&["R", "U", "F"], 11, rng),
+            Puzzle::Cube3x3 => Self::scramble_3x3(rng),
+            Puzzle::Cube4x4 => Self::scramble_big_cube(4, 40, rng),
+            Puzzle::Cube5x5 => Self::scramble_big_cube(5, 60, rng),
+            Puzzle::Cube6x6 => Self::scramble_big_cube(6, 80, rng),
+            Puzzle::Cube7x7 => Self::scramble_big_cube(7, 100, rng),
+            Puzzle::Cube3x3Bld => format!("{} {}", Self::scramble_3x3(rng), Self::random_orientation(3, rng)),
+            Puzzle::Cube4x4Bld => format!("{} {}", Self::scramble_big_cube(4, 40, rng), Self::random_orientation(4, rng)),
+            Puzzle::Cube5x5Bld => format!("{} {}", Self::scramble_big_cube(5, 60, rng), Self::random_orientation(5, rng)),
+            Puzzle::Cube3x3Fmc => Self::scramble_fmc(rng),
+            Puzzle::Relay2To4 | Puzzle::Relay2To7 => p.relay().unwrap()
+                .iter()
+                .map(|&puzzle| Self::new_scramble_with(puzzle, rng))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Puzzle::MultiBld => Self::new_multi_bld_scramble(mbld::MIN_CUBES),
         }
     }
 
-    fn scramble_3x3() -> String {
-        let scramble_length = 20;
-        let moves = ["R", "L", "U", "D", "F", "B"];
+    // One blindfolded scramble per cube, each on its own line.
+    pub fn new_multi_bld_scramble(cubes: u32) -> String {
+        let mut rng = rand::thread_rng();
+        (0..cubes)
+            .map(|_| Self::new_scramble_with(Puzzle::Cube3x3Bld, &mut rng))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn scramble_3x3(rng: &mut impl Rng) -> String {
+        Self::random_face_moves(&["R", "L", "U", "D", "F", "B"], 20, rng)
+    }
+
+    fn random_face_moves(moves: &[&str], scramble_length: usize, rng: &mut impl Rng) -> String {
         let modifiers = ["", "'", "2"];
 
-        let mut rng = rand::thread_rng();
         let mut scramble = Vec::with_capacity(scramble_length);
         let mut last_move = "";
 
@@ -37,25 +289,226 @@ impl Scrambler {
 
         scramble.join(" ")
     }
+
+    // Padded on both ends, regenerating whenever the padding could cancel
+    // against the scramble. Opposite faces commute, so the first move must
+    // not turn F or B, which would meet the padding's F, and the last must
+    // not turn R or L, which would meet its R'.
+    fn scramble_fmc(rng: &mut impl Rng) -> String {
+        loop {
+            let scramble = Self::scramble_3x3(rng);
+            let last = scramble.split_whitespace().last().unwrap();
+            if !scramble.starts_with(['F', 'B']) && !last.starts_with(['R', 'L']) {
+                return format!("{padding} {scramble} {padding}", padding = fmc::SCRAMBLE_PADDING);
+            }
+        }
+    }
+
+    // Random-move scramble using outer and wide turns. Moves on the same axis
+    // commute, so a move is rejected if the current run of moves on its axis
+    // already turned the same layers.
+    fn scramble_big_cube(size: usize, scramble_length: usize, rng: &mut impl Rng) -> String {
+        let faces = [Face::R, Face::L, Face::U, Face::D, Face::F, Face::B];
+        let mut candidates = Vec::new();
+        for depth in 1..=size / 2 {
+            for face in faces {
+                let redundant = size.is_multiple_of(2) && depth == size / 2 && matches!(face, Face::L | Face::D | Face::B);
+                if !redundant {
+                    let layers = if depth == 1 { Layers::Outer } else { Layers::Wide(depth) };
+                    candidates.push(Move { face, layers, turns: 1 });
+                }
+            }
+        }
+
+        let mut scramble: Vec<Move> = Vec::with_capacity(scramble_length);
+        let mut axis_run: Vec<Move> = Vec::new();
+
+        while scramble.len() < scramble_length {
+            let candidate = candidates[rng.gen_range(0..candidates.len())];
+            let same_axis = |m: &Move| m.face == candidate.face || m.face == candidate.face.opposite();
+
+            if !axis_run.first().is_some_and(same_axis) {
+                axis_run.clear();
+            }
+            if axis_run.iter().any(|m| m.face == candidate.face && m.layers == candidate.layers) {
+                continue;
+            }
+
+            let new_move = Move { turns: rng.gen_range(1..=3), ..candidate };
+            axis_run.push(new_move);
+            scramble.push(new_move);
+        }
+
+        format_alg(&scramble)
+    }
+
+    // Wide turns of every layer but one, which reorient the cube into one of
+    // its 24 orientations.
+    fn random_orientation(size: usize, rng: &mut impl Rng) -> String {
+        let layers = Layers::Wide(size - 1);
+        let tops = [None, Some((Face::R, 1)), Some((Face::R, 2)), Some((Face::R, 3)), Some((Face::F, 1)), Some((Face::F, 3))];
+
+        let top = tops[rng.gen_range(0..tops.len())];
+        let front = rng.gen_range(0..4);
+
+        let mut moves = Vec::new();
+        if let Some((face, turns)) = top {
+            moves.push(Move { face, layers, turns });
+        }
+        if front > 0 {
+            moves.push(Move { face: Face::U, layers, turns: front });
+        }
+
+        format_alg(&moves)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cube::{parse_alg, Cube};
+    use rand::{SeedableRng, rngs::StdRng};
 
     #[test]
     fn scramble_3x3_returns_a_20_move_scramble() {
-        let scramble = Scrambler::scramble_3x3();
+        let scramble = Scrambler::scramble_3x3(&mut rand::thread_rng());
         let moves: Vec<_> = scramble.split_whitespace().collect();
         assert_eq!(moves.len(), 20);
     }
 
     #[test]
     fn no_consecutive_repeated_moves() {
-        let scramble = Scrambler::scramble_3x3();
+        let scramble = Scrambler::scramble_3x3(&mut rand::thread_rng());
         let moves: Vec<_> = scramble.split_ascii_whitespace().collect();
         for window in moves.windows(2) {
             assert_ne!(window[0].chars().next(), window[1].chars().next());
         }
     }
+
+    #[test]
+    fn big_cube_scramble_lengths() {
+        assert_eq!(Scrambler::new_scramble(Puzzle::Cube4x4).split_whitespace().count(), 40);
+        assert_eq!(Scrambler::new_scramble(Puzzle::Cube5x5).split_whitespace().count(), 60);
+    }
+
+    #[test]
+    fn big_cube_scramble_has_no_consecutive_repeated_layers() {
+        let moves = parse_alg(&Scrambler::scramble_big_cube(5, 60, &mut rand::thread_rng())).unwrap();
+        for window in moves.windows(2) {
+            assert!(window[0].face != window[1].face || window[0].layers != window[1].layers);
+        }
+    }
+
+    #[test]
+    fn scrambles_apply_to_their_puzzle() {
+        for puzzle in Puzzle::ALL.into_iter().filter(|p| p.size().is_some()) {
+            let mut cube = Cube::new(puzzle.size().unwrap());
+            assert!(cube.apply_alg(&Scrambler::new_scramble(puzzle)).is_ok());
+        }
+    }
+
+    #[test]
+    fn relay_has_one_scramble_per_puzzle() {
+        for relay in [Puzzle::Relay2To4, Puzzle::Relay2To7] {
+            let scramble = Scrambler::new_scramble(relay);
+            let puzzles = relay.relay().unwrap();
+            assert_eq!(scramble.lines().count(), puzzles.len());
+
+            for (line, puzzle) in scramble.lines().zip(puzzles) {
+                let mut cube = Cube::new(puzzle.size().unwrap());
+                assert!(cube.apply_alg(line).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn multi_bld_has_one_scramble_per_cube() {
+        let scramble = Scrambler::new_multi_bld_scramble(5);
+        assert_eq!(scramble.lines().count(), 5);
+        for line in scramble.lines() {
+            assert!(Cube::new(3).apply_alg(line).is_ok());
+        }
+    }
+
+    #[test]
+    fn scramble_2x2_only_turns_three_faces() {
+        let scramble = Scrambler::new_scramble(Puzzle::Cube2x2);
+        assert_eq!(scramble.split_whitespace().count(), 11);
+        assert!(scramble.split_whitespace().all(|m| ["R", "U", "F"].iter().any(|f| m.starts_with(f))));
+    }
+
+    #[test]
+    fn bld_scrambles_end_with_wide_orientation_moves() {
+        for (puzzle, length) in [(Puzzle::Cube3x3Bld, 20), (Puzzle::Cube4x4Bld, 40), (Puzzle::Cube5x5Bld, 60)] {
+            let moves = parse_alg(&Scrambler::new_scramble(puzzle)).unwrap();
+            assert!(moves.len() >= length && moves.len() <= length + 2);
+            assert!(moves[length..].iter().all(|m| m.layers == Layers::Wide(puzzle.size().unwrap() - 1)));
+        }
+    }
+
+    #[test]
+    fn fmc_scramble_is_padded() {
+        let scramble = Scrambler::new_scramble(Puzzle::Cube3x3Fmc);
+        assert!(scramble.starts_with("R' U' F "));
+        assert!(scramble.ends_with(" R' U' F"));
+
+        let moves: Vec<_> = scramble.split_whitespace().collect();
+        assert_eq!(moves.len(), 26);
+        for window in moves.windows(2) {
+            assert_ne!(window[0].chars().next(), window[1].chars().next());
+        }
+
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..200 {
+            let scramble = Scrambler::scramble_fmc(&mut rng);
+            let moves: Vec<_> = scramble.split_whitespace().collect();
+            assert!(!moves[3].starts_with(['F', 'B']), "{}", scramble);
+            assert!(!moves[22].starts_with(['R', 'L']), "{}", scramble);
+        }
+    }
+
+    #[test]
+    fn filtered_scrambles_meet_constraints() {
+        let filter = ScrambleFilter { min_cross_length: 5, reject_built_pairs: true, reject_solved_pairs: true, ..Default::default() };
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..5 {
+            let (scramble, cross_length) = Scrambler::new_filtered_scramble(Puzzle::Cube3x3, &filter, &mut rng).unwrap();
+            let mut cube = Cube::new(3);
+            cube.apply_alg(&scramble).unwrap();
+
+            let difficulty = solver::difficulty(&cube, Face::U);
+            assert_eq!(cross_length, Some(difficulty.cross_length));
+            assert!(difficulty.cross_length >= 5);
+            assert_eq!(difficulty.built_pairs, 0);
+        }
+    }
+
+    #[test]
+    fn filtered_scramble_of_other_puzzles_has_no_cross_length() {
+        let (_, cross_length) = Scrambler::new_filtered_scramble(Puzzle::Cube4x4, &ScrambleFilter::hard(), &mut rand::thread_rng()).unwrap();
+        assert_eq!(cross_length, None);
+    }
+
+    #[test]
+    fn unfiltered_scrambles_measure_the_cross() {
+        let (scramble, cross_length) = Scrambler::new_filtered_scramble(Puzzle::Cube3x3, &ScrambleFilter::default(), &mut rand::thread_rng()).unwrap();
+        let mut cube = Cube::new(3);
+        cube.apply_alg(&scramble).unwrap();
+        assert_eq!(cross_length, Some(solver::cross(&cube, Face::U).len()));
+    }
+
+    #[test]
+    fn impossible_filter_is_reported() {
+        // No cross takes more than 8 moves.
+        let filter = ScrambleFilter { min_cross_length: 9, ..Default::default() };
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(Scrambler::filtered_scramble(Puzzle::Cube3x3, &filter, &mut rng, 20), Err(ScrambleError::FilterNotMet(20)));
+    }
+
+    #[test]
+    fn bld_events_use_best_of_3() {
+        assert_eq!(Puzzle::Cube3x3.format(), Format::AverageOf5);
+        assert_eq!(Puzzle::Cube3x3Bld.format(), Format::BestOf3);
+        assert_eq!(Puzzle::Cube5x5Bld.format(), Format::BestOf3);
+    }
 }
@@ -1,39 +1,100 @@
+use crate::scramble::Puzzle;
 use crate::solve::Solve;
+use crate::stats::SessionStats;
+use std::time::Duration;
 use serde::{Serialize, Deserialize};
 
 #[derive(Serialize, Deserialize)]
+#[serde(from = "StoredSession")]
 pub struct Session {
+    #[serde(default)]
+    puzzle: Puzzle,
     solves: Vec<Solve>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target: Option<Duration>,
+    #[serde(skip)]
+    stats: SessionStats,
+}
+
+// A session as saved, before its stats are worked out.
+#[derive(Deserialize)]
+struct StoredSession {
+    #[serde(default)]
+    puzzle: Puzzle,
+    solves: Vec<Solve>,
+    #[serde(default)]
+    target: Option<Duration>,
+}
+
+impl From<StoredSession> for Session {
+    fn from(stored: StoredSession) -> Self {
+        Session {
+            stats: SessionStats::from_solves(&stored.solves),
+            puzzle: stored.puzzle,
+            solves: stored.solves,
+            target: stored.target,
+        }
+    }
 }
 
 impl Session {
-    pub fn new() -> Self {
-        Session { solves: Vec::new() }
+    pub fn new(puzzle: Puzzle) -> Self {
+        Session { puzzle, solves: Vec::new(), target: None, stats: SessionStats::default() }
     }
 
+    #[cfg(test)]
     pub fn from_solves(solves: Vec<Solve>) -> Self {
-        Session { solves }
+        Session { puzzle: Puzzle::Cube3x3, stats: SessionStats::from_solves(&solves), solves, target: None }
+    }
+
+    pub fn puzzle(&self) -> Puzzle {
+        self.puzzle
     }
 
     pub fn solves(&self) -> &[Solve] {
         &self.solves
     }
 
+    pub fn stats(&self) -> &SessionStats {
+        &self.stats
+    }
+
+    // Rolling averages depend on every solve after the edited one, so the
+    // stats are rebuilt when an edit changes a result or its splits.
+    pub fn edit_solve(&mut self, index: usize, edit: impl FnOnce(&mut Solve)) {
+        let solve = &mut self.solves[index];
+        let before = (*solve.time(), solve.result(), solve.phases());
+        edit(solve);
+        if before != (*solve.time(), solve.result(), solve.phases()) {
+            self.stats = SessionStats::from_solves(&self.solves);
+        }
+    }
+
     pub fn save_solve(&mut self, solve: Solve) {
+        self.stats.push(&solve);
         self.solves.push(solve);
     }
+
+    // The time being aimed for in this session, such as sub-15.
+    pub fn target(&self) -> Option<Duration> {
+        self.target
+    }
+
+    pub fn set_target(&mut self, target: Option<Duration>) {
+        self.target = target;
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{scramble::{Puzzle, Scrambler}, solve::Penalty};
-    use std::time::Duration;
 
     #[test]
     fn new_session() {
-        let session = Session::new();
+        let session = Session::new(Puzzle::Cube3x3Bld);
         assert_eq!(session.solves().len(), 0);
+        assert_eq!(session.puzzle(), Puzzle::Cube3x3Bld);
     }
 
     #[test]
@@ -48,9 +109,25 @@ mod tests {
         assert_eq!(session.solves().len(), 3);
     }
 
+    #[test]
+    fn session_without_puzzle_defaults_to_3x3() {
+        let session: Session = serde_json::from_str(r#"{"solves":[]}"#).unwrap();
+        assert_eq!(session.puzzle(), Puzzle::Cube3x3);
+    }
+
+    #[test]
+    fn target_is_saved() {
+        let mut session = Session::new(Puzzle::Cube3x3);
+        assert!(!serde_json::to_string(&session).unwrap().contains("target"));
+
+        session.set_target(Some(Duration::from_secs(15)));
+        let session: Session = serde_json::from_str(&serde_json::to_string(&session).unwrap()).unwrap();
+        assert_eq!(session.target(), Some(Duration::from_secs(15)));
+    }
+
     #[test]
     fn save_solve() {
-        let mut session = Session::new();
+        let mut session = Session::new(Puzzle::Cube3x3);
         let scramble = Scrambler::new_scramble(Puzzle::Cube3x3);
 
         session.save_solve(Solve::build(scramble.clone(), Some(Duration::from_millis(5440)), None).unwrap());
@@ -58,5 +135,24 @@ mod tests {
         session.save_solve(Solve::build(scramble.clone(),None, Some(Penalty::DNF)).unwrap());
 
         assert_eq!(session.solves().len(), 3);
+        assert_eq!(session.stats().count(), 3);
+        assert_eq!(session.stats().best(), Some(Duration::from_millis(5440)));
+    }
+
+    #[test]
+    fn stats_are_rebuilt_when_loaded() {
+        let session = Session::from_solves(vec![Solve::build("R".to_owned(), Some(Duration::from_secs(12)), None).unwrap()]);
+        let session: Session = serde_json::from_str(&serde_json::to_string(&session).unwrap()).unwrap();
+        assert_eq!(session.stats().avg(), Some(Duration::from_secs(12)));
+    }
+
+    #[test]
+    fn edit_solve() {
+        let mut session = Session::from_solves(vec![Solve::build("R".to_owned(), Some(Duration::from_secs(12)), None).unwrap()]);
+        session.edit_solve(0, |solve| solve.set_tags(vec!["oh".to_string()]));
+        assert_eq!(session.solves()[0].tags(), ["oh"]);
+        assert_eq!(session.stats().phase_averages(), None);
+        session.edit_solve(0, |solve| solve.set_splits([2, 6, 9].map(Duration::from_secs).to_vec()));
+        assert_eq!(session.stats().phase_averages(), Some([2, 4, 3, 3].map(Duration::from_secs)));
     }
 }
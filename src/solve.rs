@@ -1,8 +1,15 @@
+use crate::mbld::MultiBldResult;
+
 use std::time::Duration;
+use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 use thiserror::Error;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+// Phases marked by splits while timing in split mode.
+pub const SPLIT_PHASES: [&str; 4] = ["Cross", "F2L", "OLL", "PLL"];
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum Penalty {
     Plus2,
     DNF
@@ -20,7 +27,25 @@ pub enum SolveError {
 pub struct Solve {
     scramble: String,
     time: Option<Duration>,
-    penalty: Option<Penalty>
+    penalty: Option<Penalty>,
+    // Fewest moves results are marked as such, as a DNF has no move count and
+    // need not keep its solution.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    fewest_moves: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    moves: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    solution: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cross_length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    splits: Vec<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    multi_bld: Option<MultiBldResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    date: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>
 }
 
 impl Solve {
@@ -38,13 +63,31 @@ impl Solve {
             }
         }
 
-        Ok(Solve { scramble, time, penalty })
+        Ok(Solve { scramble, time, penalty, fewest_moves: false, moves: None, solution: None, cross_length: None, splits: Vec::new(), multi_bld: None, date: None, tags: Vec::new() })
+    }
+
+    // Fewest moves results are a move count instead of a time, with no count
+    // meaning the attempt was a DNF.
+    pub fn build_fmc(scramble: String, solution: String, moves: Option<u32>) -> Solve {
+        let penalty = if moves.is_none() { Some(Penalty::DNF) } else { None };
+        Solve { scramble, time: None, penalty, fewest_moves: true, moves, solution: Some(solution), cross_length: None, splits: Vec::new(), multi_bld: None, date: None, tags: Vec::new() }
+    }
+
+    // Fewest moves results have no time at all, so time-based stats leave
+    // them out rather than count them as DNFs.
+    pub fn is_fewest_moves(&self) -> bool {
+        self.fewest_moves
     }
 
     pub fn scramble(&self) -> &str {
         &self.scramble
     }
 
+    // Relay solves keep one scramble per puzzle, each on its own line.
+    pub fn scrambles(&self) -> Vec<&str> {
+        self.scramble.lines().collect()
+    }
+
     pub fn time(&self) -> &Option<Duration> {
         &self.time
     }
@@ -52,6 +95,106 @@ impl Solve {
     pub fn penalty(&self) -> &Option<Penalty> {
         &self.penalty
     }
+
+    // Multi-blind attempts that do not count are DNFs, but the cube counts
+    // are kept either way.
+    pub fn build_multi_bld(scramble: String, time: Duration, result: MultiBldResult) -> Solve {
+        let (time, penalty) = if result.counts() { (Some(time), None) } else { (None, Some(Penalty::DNF)) };
+        Solve {
+            scramble,
+            time,
+            penalty,
+            fewest_moves: false,
+            moves: None,
+            solution: None,
+            cross_length: None,
+            splits: Vec::new(),
+            multi_bld: Some(result),
+            date: None,
+            tags: Vec::new()
+        }
+    }
+
+    pub fn multi_bld(&self) -> Option<&MultiBldResult> {
+        self.multi_bld.as_ref()
+    }
+
+    pub fn moves(&self) -> Option<u32> {
+        self.moves
+    }
+
+    pub fn solution(&self) -> Option<&str> {
+        self.solution.as_deref()
+    }
+
+    pub fn cross_length(&self) -> Option<usize> {
+        self.cross_length
+    }
+
+    pub fn set_cross_length(&mut self, cross_length: Option<usize>) {
+        self.cross_length = cross_length;
+    }
+
+    // When the solve was recorded. Solves saved before dates were kept have none.
+    pub fn date(&self) -> Option<DateTime<Utc>> {
+        self.date
+    }
+
+    pub fn set_date(&mut self, date: DateTime<Utc>) {
+        self.date = Some(date);
+    }
+
+    // Labels such as `oh` for one-handed solves, used to filter solves.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = tags;
+    }
+
+    // Unlike `build`, a DNF given afterwards keeps the recorded time so that
+    // it can be taken back. Solves recorded as a DNF have no time to go back
+    // to.
+    pub fn set_penalty(&mut self, penalty: Option<Penalty>) -> Result<(), SolveError> {
+        if penalty != Some(Penalty::DNF) && self.time.is_none() {
+            return Err(SolveError::NoTimeWithoutDNF);
+        }
+        self.penalty = penalty;
+        Ok(())
+    }
+
+    pub fn set_splits(&mut self, splits: Vec<Duration>) {
+        self.splits = splits;
+    }
+
+    // Duration of each phase, from the splits marked during the solve up to
+    // the final time. Empty when the solve has no splits or is a DNF.
+    pub fn phases(&self) -> Vec<Duration> {
+        match self.time {
+            Some(time) if !self.splits.is_empty() && self.penalty != Some(Penalty::DNF) => {
+                let mut boundaries = self.splits.clone();
+                boundaries.push(time);
+                boundaries.iter()
+                    .scan(Duration::ZERO, |previous, &split| {
+                        let phase = split.saturating_sub(*previous);
+                        *previous = split;
+                        Some(phase)
+                    })
+                    .collect()
+            },
+            _ => Vec::new()
+        }
+    }
+
+    pub fn result(&self) -> Option<Duration> {
+        match (self.time, &self.penalty) {
+            (_, Some(Penalty::DNF)) => None,
+            (Some(time), Some(Penalty::Plus2)) => Some(time + Duration::from_secs(2)),
+            (time, None) => time,
+            (None, _) => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -84,6 +227,56 @@ mod tests {
         assert!(solve.is_ok());
     }
 
+    #[test]
+    fn result_applies_penalty() {
+        let time = Some(Duration::from_secs(10));
+        assert_eq!(Solve::build("R".to_owned(), time, None).unwrap().result(), time);
+        assert_eq!(Solve::build("R".to_owned(), time, Some(Penalty::Plus2)).unwrap().result(), Some(Duration::from_secs(12)));
+        assert_eq!(Solve::build("R".to_owned(), None, Some(Penalty::DNF)).unwrap().result(), None);
+    }
+
+    #[test]
+    fn phases_from_splits() {
+        let mut solve = Solve::build("R U".to_owned(), Some(Duration::from_millis(12000)), None).unwrap();
+        assert!(solve.phases().is_empty());
+
+        solve.set_splits(vec![Duration::from_millis(2000), Duration::from_millis(8500), Duration::from_millis(10000)]);
+        assert_eq!(solve.phases(), vec![
+            Duration::from_millis(2000),
+            Duration::from_millis(6500),
+            Duration::from_millis(1500),
+            Duration::from_millis(2000),
+        ]);
+    }
+
+    #[test]
+    fn relay_solve_keeps_each_scramble() {
+        let solve = Solve::build("R U F\nR U R' U'".to_owned(), Some(Duration::from_secs(60)), None).unwrap();
+        assert_eq!(solve.scrambles(), vec!["R U F", "R U R' U'"]);
+    }
+
+    #[test]
+    fn fmc_solve() {
+        let solve = Solve::build_fmc("R' U' F R U R' U' F".to_owned(), "F' U R U' R' F' U R".to_owned(), Some(8));
+        assert_eq!(solve.moves(), Some(8));
+        assert_eq!(solve.solution(), Some("F' U R U' R' F' U R"));
+        assert!(solve.penalty().is_none());
+
+        let solve = Solve::build_fmc("R' U' F R U R' U' F".to_owned(), String::new(), None);
+        assert!(matches!(solve.penalty(), Some(Penalty::DNF)));
+    }
+
+    #[test]
+    fn multi_bld_solve() {
+        let time = Duration::from_secs(40 * 60);
+        let solve = Solve::build_multi_bld("R\nU".to_owned(), time, MultiBldResult { solved: 2, attempted: 2 });
+        assert_eq!(solve.result(), Some(time));
+
+        let solve = Solve::build_multi_bld("R\nU".to_owned(), time, MultiBldResult { solved: 1, attempted: 2 });
+        assert_eq!(solve.result(), None);
+        assert_eq!(solve.multi_bld(), Some(&MultiBldResult { solved: 1, attempted: 2 }));
+    }
+
     #[test]
     fn invalid_solve_time_with_dnf() {
         let solve = Solve::build(
@@ -103,4 +296,22 @@ mod tests {
         );
         assert!(matches!(solve, Err(SolveError::NoTimeWithoutDNF)));
     }
+
+    #[test]
+    fn change_penalty() {
+        let mut solve = Solve::build("R U".to_owned(), Some(Duration::from_secs(10)), None).unwrap();
+        solve.set_penalty(Some(Penalty::Plus2)).unwrap();
+        assert_eq!(solve.result(), Some(Duration::from_secs(12)));
+        solve.set_penalty(None).unwrap();
+        assert_eq!(solve.result(), Some(Duration::from_secs(10)));
+
+        solve.set_penalty(Some(Penalty::DNF)).unwrap();
+        assert_eq!(solve.result(), None);
+        assert_eq!(solve.time(), &Some(Duration::from_secs(10)));
+        solve.set_penalty(None).unwrap();
+        assert_eq!(solve.result(), Some(Duration::from_secs(10)));
+
+        let mut dnf = Solve::build("R U".to_owned(), None, Some(Penalty::DNF)).unwrap();
+        assert!(matches!(dnf.set_penalty(None), Err(SolveError::NoTimeWithoutDNF)));
+    }
 }
@@ -0,0 +1,482 @@
+use crate::cube::{Cube, Face, Layers, Move, MoveError};
+
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+const SLOTS: usize = 24;
+const UNVISITED: u8 = u8::MAX;
+const MAX_DEPTH: u8 = 20;
+
+#[derive(Debug)]
+pub struct CrossAnalysis {
+    pub crosses: Vec<(Face, Vec<Move>)>,
+    pub xcross: (Face, Vec<Move>)
+}
+
+pub fn analyse(scramble: &str) -> Result<CrossAnalysis, MoveError> {
+    let mut cube = Cube::new(3);
+    cube.apply_alg(scramble)?;
+
+    let crosses: Vec<_> = Face::ALL.iter()
+        .map(|&colour| (colour, cross(&cube, colour)))
+        .collect();
+
+    let (best_colour, _) = crosses.iter().min_by_key(|(_, moves)| moves.len()).unwrap();
+    let xcross = (*best_colour, xcross(&cube, *best_colour));
+
+    Ok(CrossAnalysis { crosses, xcross })
+}
+
+pub fn cross(cube: &Cube, colour: Face) -> Vec<Move> {
+    solve(cube, colour, false)
+}
+
+pub fn xcross(cube: &Cube, colour: Face) -> Vec<Move> {
+    solve(cube, colour, true)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Difficulty {
+    pub cross_length: usize,
+    pub built_pairs: usize,
+    pub solved_pairs: usize
+}
+
+// Counts the F2L pairs of the given cross colour that are already connected
+// anywhere on the cube, and those already solved in their slot.
+pub fn difficulty(cube: &Cube, colour: Face) -> Difficulty {
+    let tables = tables();
+    let (rotated, _) = rotate_to_bottom(cube, colour);
+    let state = SearchState::read(&rotated, tables);
+
+    let solved_pairs = (0..4)
+        .filter(|&i| tables.pairs[i][pair_index(state.corners[i], state.edges[i])] == 0)
+        .count();
+    let built_pairs = (0..4)
+        .filter(|&i| pair_is_built(&rotated, tables, state.corners[i], state.edges[i]))
+        .count();
+
+    Difficulty { cross_length: cross(cube, colour).len(), built_pairs, solved_pairs }
+}
+
+fn pair_is_built(cube: &Cube, tables: &Tables, corner: u8, edge: u8) -> bool {
+    fn cubie_of(cubies: &[Vec<usize>], sticker: usize) -> &Vec<usize> {
+        cubies.iter().find(|c| c.contains(&sticker)).unwrap()
+    }
+    let corner = cubie_of(&tables.corner_cubies, tables.corner_stickers[corner as usize]);
+    let edge = cubie_of(&tables.edge_cubies, tables.edge_stickers[edge as usize]);
+
+    edge.iter().all(|&e| {
+        corner.iter().any(|&c| {
+            let (corner_pos, corner_face) = tables.stickers[c];
+            let (edge_pos, edge_face) = tables.stickers[e];
+            let adjacent = corner_pos.iter().zip(edge_pos).filter(|(a, b)| *a != b).count() == 1;
+            adjacent && corner_face == edge_face && cube.facelets()[c] == cube.facelets()[e]
+        })
+    })
+}
+
+fn rotate_to_bottom(cube: &Cube, colour: Face) -> (Cube, Option<Move>) {
+    let centre = Face::ALL.iter().copied()
+        .find(|&face| cube.facelet(face, 1, 1) == colour)
+        .unwrap();
+    let rotation = rotation_to_bottom(centre);
+
+    let mut rotated = cube.clone();
+    if let Some(rotation) = &rotation {
+        rotated.apply(rotation).unwrap();
+    }
+    (rotated, rotation)
+}
+
+// Searches with the cross colour moved to D and translates the solution back
+// to the orientation the cube was scrambled in.
+fn solve(cube: &Cube, colour: Face, with_pair: bool) -> Vec<Move> {
+    let tables = tables();
+    let (rotated, rotation) = rotate_to_bottom(cube, colour);
+
+    let state = SearchState::read(&rotated, tables);
+    let solution = if with_pair {
+        ida_star(state, tables, |s| s.cross_distance(tables).max(s.pair_distance(tables)))
+    } else {
+        ida_star(state, tables, |s| s.cross_distance(tables))
+    };
+
+    solution.into_iter()
+        .map(|m| {
+            let face = match &rotation {
+                Some(rotation) => Face::ALL.iter().copied().find(|&f| rotation.map_face(f) == m.face).unwrap(),
+                None => m.face,
+            };
+            Move { face, ..m }
+        })
+        .collect()
+}
+
+fn rotation_to_bottom(face: Face) -> Option<Move> {
+    let (axis, turns) = match face {
+        Face::D => return None,
+        Face::U => (Face::R, 2),
+        Face::F => (Face::R, 3),
+        Face::B => (Face::R, 1),
+        Face::R => (Face::F, 1),
+        Face::L => (Face::F, 3),
+    };
+    Some(Move { face: axis, layers: Layers::All, turns })
+}
+
+fn ida_star(start: SearchState, tables: &Tables, heuristic: impl Fn(&SearchState) -> u8) -> Vec<Move> {
+    let mut path = Vec::new();
+    for bound in heuristic(&start)..=MAX_DEPTH {
+        if search(&start, bound, &mut path, tables, &heuristic) {
+            break;
+        }
+    }
+    path.into_iter().map(|m| tables.moves[m]).collect()
+}
+
+fn search(
+    state: &SearchState,
+    remaining: u8,
+    path: &mut Vec<usize>,
+    tables: &Tables,
+    heuristic: &impl Fn(&SearchState) -> u8
+) -> bool {
+    let h = heuristic(state);
+    if h == 0 {
+        return true;
+    }
+    if h > remaining {
+        return false;
+    }
+
+    for (index, m) in tables.moves.iter().enumerate() {
+        if let Some(&last) = path.last() {
+            let last: Face = tables.moves[last].face;
+            let commutes_backwards = m.face == last.opposite() && m.face as u8 > last as u8;
+            if m.face == last || commutes_backwards {
+                continue;
+            }
+        }
+
+        path.push(index);
+        if search(&state.apply(index, tables), remaining - 1, path, tables, heuristic) {
+            return true;
+        }
+        path.pop();
+    }
+
+    false
+}
+
+// Positions of the tracked pieces, each given as the edge or corner sticker
+// slot currently holding the piece's reference sticker.
+#[derive(Debug, Clone, Copy)]
+struct SearchState {
+    cross: [u8; 4],
+    corners: [u8; 4],
+    edges: [u8; 4]
+}
+
+impl SearchState {
+    fn read(cube: &Cube, tables: &Tables) -> Self {
+        let centre = |face: Face| cube.facelet(face, 1, 1);
+        let find = |cubies: &[Vec<usize>], colours: &[Face], reference: Face, slots: &[Option<u8>]| {
+            let cubie = cubies.iter()
+                .find(|stickers| {
+                    stickers.len() == colours.len()
+                        && stickers.iter().all(|&s| colours.contains(&cube.facelets()[s]))
+                })
+                .unwrap();
+            let sticker = cubie.iter().find(|&&s| cube.facelets()[s] == reference).unwrap();
+            slots[*sticker].unwrap()
+        };
+
+        let down = centre(Face::D);
+        let cross = tables.cross_sides.map(|side| {
+            find(&tables.edge_cubies, &[down, centre(side)], down, &tables.edge_slot)
+        });
+        let corners = tables.pair_sides.map(|(a, b)| {
+            find(&tables.corner_cubies, &[down, centre(a), centre(b)], down, &tables.corner_slot)
+        });
+        let edges = tables.pair_sides.map(|(a, b)| {
+            find(&tables.edge_cubies, &[centre(a), centre(b)], centre(a), &tables.edge_slot)
+        });
+
+        SearchState { cross, corners, edges }
+    }
+
+    fn apply(&self, m: usize, tables: &Tables) -> Self {
+        let edge = |slot: u8| tables.edge_moves[m][slot as usize];
+        let corner = |slot: u8| tables.corner_moves[m][slot as usize];
+
+        SearchState {
+            cross: self.cross.map(edge),
+            corners: self.corners.map(corner),
+            edges: self.edges.map(edge)
+        }
+    }
+
+    fn cross_distance(&self, tables: &Tables) -> u8 {
+        tables.cross[cross_index(self.cross)]
+    }
+
+    fn pair_distance(&self, tables: &Tables) -> u8 {
+        (0..4)
+            .map(|i| tables.pairs[i][pair_index(self.corners[i], self.edges[i])])
+            .min()
+            .unwrap()
+    }
+}
+
+fn cross_index(cross: [u8; 4]) -> usize {
+    cross.iter().fold(0, |index, &slot| index * SLOTS + slot as usize)
+}
+
+fn pair_index(corner: u8, edge: u8) -> usize {
+    corner as usize * SLOTS + edge as usize
+}
+
+struct Tables {
+    moves: Vec<Move>,
+    stickers: Vec<([i32; 3], Face)>,
+    edge_stickers: Vec<usize>,
+    corner_stickers: Vec<usize>,
+    edge_cubies: Vec<Vec<usize>>,
+    corner_cubies: Vec<Vec<usize>>,
+    edge_slot: Vec<Option<u8>>,
+    corner_slot: Vec<Option<u8>>,
+    edge_moves: Vec<[u8; SLOTS]>,
+    corner_moves: Vec<[u8; SLOTS]>,
+    cross_sides: [Face; 4],
+    pair_sides: [(Face, Face); 4],
+    cross: Vec<u8>,
+    pairs: Vec<Vec<u8>>
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(Tables::build)
+}
+
+impl Tables {
+    fn build() -> Self {
+        let cube = Cube::new(3);
+        let stickers: Vec<_> = (0..cube.facelets().len()).map(|i| cube.sticker(i)).collect();
+
+        let cubies = |zeros: usize| {
+            let mut cubies: Vec<Vec<usize>> = Vec::new();
+            for (index, (pos, _)) in stickers.iter().enumerate() {
+                if pos.iter().filter(|&&c| c == 0).count() != zeros {
+                    continue;
+                }
+                match cubies.iter_mut().find(|c| stickers[c[0]].0 == *pos) {
+                    Some(cubie) => cubie.push(index),
+                    None => cubies.push(vec![index]),
+                }
+            }
+            cubies
+        };
+        let edge_cubies = cubies(1);
+        let corner_cubies = cubies(0);
+
+        let slots = |cubies: &[Vec<usize>]| {
+            let mut slots = vec![None; stickers.len()];
+            for (slot, &sticker) in cubies.iter().flatten().enumerate() {
+                slots[sticker] = Some(slot as u8);
+            }
+            slots
+        };
+        let edge_slot = slots(&edge_cubies);
+        let corner_slot = slots(&corner_cubies);
+
+        let moves: Vec<_> = Face::ALL.iter()
+            .flat_map(|&face| (1..=3).map(move |turns| Move::outer(face, turns)))
+            .collect();
+
+        let move_table = |cubies: &[Vec<usize>], slots: &[Option<u8>]| -> Vec<[u8; SLOTS]> {
+            moves.iter()
+                .map(|m| {
+                    let perm = cube.permutation(m).unwrap();
+                    let mut table = [0; SLOTS];
+                    for (slot, &sticker) in cubies.iter().flatten().enumerate() {
+                        table[slot] = slots[perm[sticker]].unwrap();
+                    }
+                    table
+                })
+                .collect()
+        };
+        let edge_moves = move_table(&edge_cubies, &edge_slot);
+        let corner_moves = move_table(&corner_cubies, &corner_slot);
+
+        let on_face = |cubie: &Vec<usize>, face: Face| cubie.iter().copied().find(|&s| stickers[s].1 == face);
+        let sides = |cubie: &Vec<usize>| {
+            cubie.iter().map(|&s| stickers[s].1).filter(|&f| f != Face::D).collect::<Vec<_>>()
+        };
+
+        let down_edges: Vec<_> = edge_cubies.iter().filter(|c| on_face(c, Face::D).is_some()).collect();
+        let cross_sides = [0, 1, 2, 3].map(|i| sides(down_edges[i])[0]);
+        let cross_goal = [0, 1, 2, 3].map(|i| edge_slot[on_face(down_edges[i], Face::D).unwrap()].unwrap());
+
+        let down_corners: Vec<_> = corner_cubies.iter().filter(|c| on_face(c, Face::D).is_some()).collect();
+        let pair_sides = [0, 1, 2, 3].map(|i| {
+            let sides = sides(down_corners[i]);
+            (sides[0], sides[1])
+        });
+        let pair_goals = [0, 1, 2, 3].map(|i| {
+            let (a, b) = pair_sides[i];
+            let corner = corner_slot[on_face(down_corners[i], Face::D).unwrap()].unwrap();
+            let edge = edge_cubies.iter()
+                .find(|c| c.len() == 2 && on_face(c, a).is_some() && on_face(c, b).is_some())
+                .map(|c| edge_slot[on_face(c, a).unwrap()].unwrap())
+                .unwrap();
+            (corner, edge)
+        });
+
+        let cross = distance_table(SLOTS.pow(4), cross_index(cross_goal), |index, m| {
+            let mut slots = [0u8; 4];
+            let mut rest = index;
+            for slot in slots.iter_mut().rev() {
+                *slot = (rest % SLOTS) as u8;
+                rest /= SLOTS;
+            }
+            cross_index(slots.map(|s| edge_moves[m][s as usize]))
+        }, moves.len());
+
+        let pairs = pair_goals.iter()
+            .map(|&(corner, edge)| {
+                distance_table(SLOTS * SLOTS, pair_index(corner, edge), |index, m| {
+                    let corner = corner_moves[m][index / SLOTS];
+                    let edge = edge_moves[m][index % SLOTS];
+                    pair_index(corner, edge)
+                }, moves.len())
+            })
+            .collect();
+
+        Tables {
+            moves,
+            edge_stickers: edge_cubies.iter().flatten().copied().collect(),
+            corner_stickers: corner_cubies.iter().flatten().copied().collect(),
+            stickers,
+            edge_cubies,
+            corner_cubies,
+            edge_slot,
+            corner_slot,
+            edge_moves,
+            corner_moves,
+            cross_sides,
+            pair_sides,
+            cross,
+            pairs
+        }
+    }
+}
+
+fn distance_table(size: usize, goal: usize, next: impl Fn(usize, usize) -> usize, move_count: usize) -> Vec<u8> {
+    let mut table = vec![UNVISITED; size];
+    let mut queue = VecDeque::from([goal]);
+    table[goal] = 0;
+
+    while let Some(index) = queue.pop_front() {
+        for m in 0..move_count {
+            let next = next(index, m);
+            if table[next] == UNVISITED {
+                table[next] = table[index] + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::parse_alg;
+
+    fn scrambled(alg: &str) -> Cube {
+        let mut cube = Cube::new(3);
+        cube.apply_alg(alg).unwrap();
+        cube
+    }
+
+    fn cross_is_solved(cube: &Cube, colour: Face) -> bool {
+        let tables = tables();
+        let (rotated, _) = rotate_to_bottom(cube, colour);
+        SearchState::read(&rotated, tables).cross_distance(tables) == 0
+    }
+
+    #[test]
+    fn solved_cube_needs_no_moves() {
+        let cube = Cube::new(3);
+        for colour in Face::ALL {
+            assert!(cross(&cube, colour).is_empty());
+        }
+        assert!(xcross(&cube, Face::D).is_empty());
+    }
+
+    #[test]
+    fn cross_undoes_short_scramble() {
+        let cube = scrambled("R2 F");
+        assert_eq!(cross(&cube, Face::D), parse_alg("F' R2").unwrap());
+    }
+
+    #[test]
+    fn cross_solutions_solve_the_cross() {
+        let cube = scrambled("D2 F' R2 U' B L2 F' D R U2 B2 L' F2 D' R' B U L2 F R");
+        for colour in Face::ALL {
+            let solution = cross(&cube, colour);
+            assert!(solution.len() <= 8);
+
+            let mut solved = cube.clone();
+            solved.apply_moves(&solution).unwrap();
+            assert!(cross_is_solved(&solved, colour), "{} cross not solved", colour.colour_name());
+        }
+    }
+
+    #[test]
+    fn cross_on_rotated_cube() {
+        let cube = scrambled("R U F Rw Uw'");
+        let solution = cross(&cube, Face::U);
+
+        let mut solved = cube.clone();
+        solved.apply_moves(&solution).unwrap();
+        assert!(cross_is_solved(&solved, Face::U));
+    }
+
+    #[test]
+    fn xcross_solves_cross_and_a_pair() {
+        let cube = scrambled("F R U' L2 D B'");
+        let solution = xcross(&cube, Face::D);
+        assert!(solution.len() >= cross(&cube, Face::D).len());
+        assert!(solution.len() <= 6);
+
+        let mut solved = cube.clone();
+        solved.apply_moves(&solution).unwrap();
+        let tables = tables();
+        let state = SearchState::read(&solved, tables);
+        assert_eq!(state.cross_distance(tables), 0);
+        assert_eq!(state.pair_distance(tables), 0);
+    }
+
+    #[test]
+    fn difficulty_of_solved_cube() {
+        assert_eq!(difficulty(&Cube::new(3), Face::U), Difficulty { cross_length: 0, built_pairs: 4, solved_pairs: 4 });
+    }
+
+    #[test]
+    fn difficulty_counts_built_and_solved_pairs() {
+        // R U R' takes the front-right pair out of its slot without breaking it.
+        assert_eq!(difficulty(&scrambled("R U R'"), Face::D), Difficulty { cross_length: 0, built_pairs: 4, solved_pairs: 3 });
+
+        // R U' R' splits the front-right pair.
+        assert_eq!(difficulty(&scrambled("R U' R'"), Face::D), Difficulty { cross_length: 0, built_pairs: 3, solved_pairs: 3 });
+    }
+
+    #[test]
+    fn analyse_rejects_big_cube_scrambles() {
+        assert!(analyse("4Rw U").is_err());
+        assert_eq!(analyse("R2 F").unwrap().crosses.len(), 6);
+    }
+}
@@ -0,0 +1,395 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+use hound::{SampleFormat, WavReader};
+use thiserror::Error;
+
+// Stackmat timers send packets over the audio cable as 1200 baud serial
+// data, 8 data bits, no parity, one stop bit.
+pub const BAUD_RATE: u32 = 1200;
+
+// Packets are separated by the line idling for longer than any byte.
+const IDLE_BITS: f64 = 10.0;
+// Samples this close to zero keep the previous level, so noise on a silent
+// line does not look like start bits.
+const HYSTERESIS: f32 = 0.02;
+
+#[derive(Error, Debug)]
+pub enum StackmatError {
+    #[error("Packet has {0} bytes, expected 9 or 10")]
+    InvalidLength(usize),
+    #[error("Unknown timer status: {0:?}")]
+    InvalidStatus(char),
+    #[error("Invalid digit in packet: {0:?}")]
+    InvalidDigit(char),
+    #[error("Packet checksum does not match its digits")]
+    Checksum,
+    #[error("Packet is not terminated by a line break")]
+    Unterminated,
+    #[error("WAV error: {0}")]
+    Wav(#[from] hound::Error)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Idle,
+    Ready,
+    HandsOn,
+    LeftHand,
+    RightHand,
+    Running,
+    Stopped
+}
+
+impl Status {
+    fn from_byte(byte: u8) -> Result<Status, StackmatError> {
+        match byte {
+            b'I' => Ok(Status::Idle),
+            b'A' => Ok(Status::Ready),
+            b'C' => Ok(Status::HandsOn),
+            b'L' => Ok(Status::LeftHand),
+            b'R' => Ok(Status::RightHand),
+            b' ' => Ok(Status::Running),
+            b'S' => Ok(Status::Stopped),
+            other => Err(StackmatError::InvalidStatus(other as char)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Packet {
+    pub status: Status,
+    pub time: Duration
+}
+
+// Gen 3 packets are a status byte, the digits M SS D C, a checksum of 64
+// plus the digit sum, then `\n\r`. Gen 4 timers add a millisecond digit.
+pub fn parse_packet(bytes: &[u8]) -> Result<Packet, StackmatError> {
+    if bytes.len() != 9 && bytes.len() != 10 {
+        return Err(StackmatError::InvalidLength(bytes.len()));
+    }
+    if !bytes.ends_with(b"\n\r") {
+        return Err(StackmatError::Unterminated);
+    }
+
+    let status = Status::from_byte(bytes[0])?;
+    let checksum = bytes[bytes.len() - 3];
+    let digits = bytes[1..bytes.len() - 3].iter()
+        .map(|&byte| match byte {
+            b'0'..=b'9' => Ok((byte - b'0') as u64),
+            other => Err(StackmatError::InvalidDigit(other as char)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if 64 + digits.iter().sum::<u64>() != checksum as u64 {
+        return Err(StackmatError::Checksum);
+    }
+
+    let millis = digits[0] * 60_000
+        + (digits[1] * 10 + digits[2]) * 1000
+        + digits[3] * 100
+        + digits[4] * 10
+        + digits.get(5).copied().unwrap_or(0);
+
+    Ok(Packet { status, time: Duration::from_millis(millis) })
+}
+
+// Anything that produces mono PCM samples in the range -1.0..=1.0, such as
+// a recording or, later, a sound card input.
+pub trait AudioSource {
+    fn sample_rate(&self) -> u32;
+    // Fills the buffer and returns how many samples were read, 0 at the end.
+    fn read(&mut self, buffer: &mut [f32]) -> Result<usize, StackmatError>;
+}
+
+// Reads the first channel of a WAV recording, at the speed it was recorded
+// so the timer runs as it did on the line.
+pub struct WavSource {
+    reader: WavReader<BufReader<File>>,
+    channels: usize,
+    channel: usize,
+    scale: f32,
+    started: Option<Instant>,
+    samples_read: u64
+}
+
+impl WavSource {
+    pub fn open(path: &Path) -> Result<WavSource, StackmatError> {
+        let reader = WavReader::open(path)?;
+        let spec = reader.spec();
+        let scale = match spec.sample_format {
+            SampleFormat::Float => 1.0,
+            SampleFormat::Int => (1_i64 << (spec.bits_per_sample - 1)) as f32,
+        };
+        Ok(WavSource { reader, channels: spec.channels as usize, channel: 0, scale, started: None, samples_read: 0 })
+    }
+}
+
+impl AudioSource for WavSource {
+    fn sample_rate(&self) -> u32 {
+        self.reader.spec().sample_rate
+    }
+
+    fn read(&mut self, buffer: &mut [f32]) -> Result<usize, StackmatError> {
+        let float = self.reader.spec().sample_format == SampleFormat::Float;
+        let mut read = 0;
+
+        while read < buffer.len() {
+            let sample = if float {
+                self.reader.samples::<f32>().next().transpose()?
+            } else {
+                self.reader.samples::<i32>().next().transpose()?.map(|s| s as f32 / self.scale)
+            };
+            let Some(sample) = sample else { break };
+
+            if self.channel == 0 {
+                buffer[read] = sample;
+                read += 1;
+            }
+            self.channel = (self.channel + 1) % self.channels;
+        }
+
+        // Hands the samples over no sooner than the last of them was played.
+        let started = *self.started.get_or_insert_with(Instant::now);
+        self.samples_read += read as u64;
+        let due = Duration::from_secs_f64(self.samples_read as f64 / self.sample_rate() as f64);
+        if let Some(wait) = due.checked_sub(started.elapsed()) {
+            thread::sleep(wait);
+        }
+
+        Ok(read)
+    }
+}
+
+struct Frame {
+    elapsed: usize,
+    bits: u16,
+    count: u32
+}
+
+// Turns a stream of samples into packets, keeping its state between calls
+// so audio can be fed in chunks as it arrives. The line level while idle is
+// taken as the mark level, which makes it work with either polarity.
+pub struct Decoder {
+    bit_samples: f64,
+    idle_samples: usize,
+    level: bool,
+    run: usize,
+    mark: Option<bool>,
+    frame: Option<Frame>,
+    bytes: Vec<u8>,
+    valid: bool
+}
+
+impl Decoder {
+    pub fn new(sample_rate: u32) -> Decoder {
+        let bit_samples = sample_rate as f64 / BAUD_RATE as f64;
+        Decoder {
+            bit_samples,
+            idle_samples: (IDLE_BITS * bit_samples).ceil() as usize,
+            level: false,
+            run: 0,
+            mark: None,
+            frame: None,
+            bytes: Vec::new(),
+            valid: true
+        }
+    }
+
+    pub fn feed(&mut self, samples: &[f32]) -> Vec<Packet> {
+        let mut packets = Vec::new();
+
+        for &sample in samples {
+            let level = if sample > HYSTERESIS {
+                true
+            } else if sample < -HYSTERESIS {
+                false
+            } else {
+                self.level
+            };
+            if level == self.level {
+                self.run += 1;
+            } else {
+                self.level = level;
+                self.run = 1;
+            }
+
+            match self.frame.as_mut() {
+                Some(frame) => {
+                    frame.elapsed += 1;
+                    // Bits are read in their middle, the first data bit
+                    // starting one bit after the start bit's edge.
+                    if frame.elapsed as f64 >= (frame.count as f64 + 1.5) * self.bit_samples {
+                        if Some(level) == self.mark {
+                            frame.bits |= 1 << frame.count;
+                        }
+                        frame.count += 1;
+                    }
+                    if frame.count == 9 {
+                        // The ninth bit is the stop bit, which must be a mark.
+                        if frame.bits & 1 << 8 == 0 {
+                            self.valid = false;
+                        }
+                        self.bytes.push(frame.bits as u8);
+                        self.frame = None;
+                    }
+                },
+                None if self.run >= self.idle_samples => {
+                    self.mark = Some(level);
+                    if !self.bytes.is_empty() {
+                        packets.extend(self.end_packet());
+                    }
+                },
+                None if self.run == 1 && self.mark == Some(!level) => {
+                    self.frame = Some(Frame { elapsed: 0, bits: 0, count: 0 });
+                },
+                None => {}
+            }
+        }
+
+        packets
+    }
+
+    fn end_packet(&mut self) -> Option<Packet> {
+        let packet = if self.valid { parse_packet(&self.bytes).ok() } else { None };
+        self.bytes.clear();
+        self.valid = true;
+        packet
+    }
+}
+
+// Decodes a source on its own thread, sending each packet as it is found.
+// The thread stops at the end of the source or when the receiver is dropped.
+pub fn spawn(mut source: Box<dyn AudioSource + Send>) -> Receiver<Packet> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut decoder = Decoder::new(source.sample_rate());
+        let mut buffer = vec![0.0; (source.sample_rate() / 100).max(1) as usize];
+
+        while let Ok(read) = source.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+            for packet in decoder.feed(&buffer[..read]) {
+                if sender.send(packet).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_dir;
+    use hound::{WavSpec, WavWriter};
+
+    fn packet_bytes(status: u8, digits: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![status];
+        bytes.extend(digits.iter().map(|d| b'0' + d));
+        bytes.push(64 + digits.iter().sum::<u8>());
+        bytes.extend(b"\n\r");
+        bytes
+    }
+
+    // Serial encoding of packets with an idle gap before each, as the
+    // timer would put on the line.
+    fn encode(packets: &[Vec<u8>], sample_rate: u32, mark: f32) -> Vec<f32> {
+        let bit_samples = sample_rate as f64 / BAUD_RATE as f64;
+        let mut bits = Vec::new();
+        for packet in packets {
+            bits.extend([true; 20]);
+            for &byte in packet {
+                bits.push(false);
+                bits.extend((0..8).map(|i| byte >> i & 1 == 1));
+                bits.push(true);
+            }
+        }
+        bits.extend([true; 20]);
+
+        let length = (bits.len() as f64 * bit_samples) as usize;
+        (0..length)
+            .map(|i| if bits[(i as f64 / bit_samples) as usize] { mark } else { -mark })
+            .collect()
+    }
+
+    #[test]
+    fn parse_gen3_packet() {
+        let packet = parse_packet(&packet_bytes(b'S', &[1, 2, 3, 4, 5])).unwrap();
+        assert_eq!(packet, Packet { status: Status::Stopped, time: Duration::from_millis(83450) });
+    }
+
+    #[test]
+    fn parse_gen4_packet() {
+        let packet = parse_packet(&packet_bytes(b' ', &[0, 0, 9, 8, 7, 6])).unwrap();
+        assert_eq!(packet, Packet { status: Status::Running, time: Duration::from_millis(9876) });
+    }
+
+    #[test]
+    fn parse_invalid_packets() {
+        let mut bad_checksum = packet_bytes(b'S', &[0, 1, 2, 3, 4]);
+        bad_checksum[6] += 1;
+        assert!(matches!(parse_packet(&bad_checksum), Err(StackmatError::Checksum)));
+        assert!(matches!(parse_packet(&packet_bytes(b'X', &[0, 1, 2, 3, 4])), Err(StackmatError::InvalidStatus('X'))));
+        assert!(matches!(parse_packet(b"S0123"), Err(StackmatError::InvalidLength(5))));
+        assert!(matches!(parse_packet(b"S01a34Z\n\r"), Err(StackmatError::InvalidDigit('a'))));
+        assert!(matches!(parse_packet(b"S01234Z\r\n"), Err(StackmatError::Unterminated)));
+    }
+
+    #[test]
+    fn decode_samples_in_chunks() {
+        let packets = [packet_bytes(b'I', &[0, 0, 0, 0, 0]), packet_bytes(b' ', &[0, 0, 1, 5, 0]), packet_bytes(b'S', &[0, 1, 2, 3, 4])];
+
+        for mark in [0.5, -0.5] {
+            let samples = encode(&packets, 44100, mark);
+            let mut decoder = Decoder::new(44100);
+            let decoded: Vec<_> = samples.chunks(441).flat_map(|chunk| decoder.feed(chunk)).collect();
+
+            assert_eq!(decoded, vec![
+                Packet { status: Status::Idle, time: Duration::ZERO },
+                Packet { status: Status::Running, time: Duration::from_millis(1500) },
+                Packet { status: Status::Stopped, time: Duration::from_millis(12340) },
+            ]);
+        }
+    }
+
+    #[test]
+    fn decode_skips_corrupted_packets() {
+        let packets = [packet_bytes(b' ', &[0, 0, 1, 5, 0]), packet_bytes(b'S', &[0, 1, 2, 3, 4])];
+        let mut samples = encode(&packets, 8000, 0.5);
+        // Corrupt the first packet.
+        let bit_samples = 8000 / BAUD_RATE as usize;
+        for sample in &mut samples[bit_samples * 32..bit_samples * 34] {
+            *sample = -*sample;
+        }
+
+        let decoded = Decoder::new(8000).feed(&samples);
+        assert_eq!(decoded, vec![Packet { status: Status::Stopped, time: Duration::from_millis(12340) }]);
+    }
+
+    #[test]
+    fn decode_wav_recording() {
+        let path = test_dir("stackmat-recording").join("recording.wav");
+        let spec = WavSpec { channels: 2, sample_rate: 22050, bits_per_sample: 16, sample_format: SampleFormat::Int };
+        let mut writer = WavWriter::create(&path, spec).unwrap();
+        for sample in encode(&[packet_bytes(b'S', &[0, 0, 9, 8, 7, 6])], 22050, 0.8) {
+            let sample = (sample * i16::MAX as f32) as i16;
+            writer.write_sample(sample).unwrap();
+            writer.write_sample(0_i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let start = Instant::now();
+        let packets: Vec<_> = spawn(Box::new(WavSource::open(&path).unwrap())).iter().collect();
+        assert_eq!(packets, vec![Packet { status: Status::Stopped, time: Duration::from_millis(9876) }]);
+        // 140 bits at 1200 baud take about 117ms to play.
+        assert!(start.elapsed() >= Duration::from_millis(110));
+    }
+}
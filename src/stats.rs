@@ -0,0 +1,368 @@
+use crate::math::{self, rank};
+use crate::solve::{Solve, SPLIT_PHASES};
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+fn insert_sorted(sorted: &mut Vec<Option<Duration>>, result: Option<Duration>) {
+    let position = sorted.partition_point(|r| rank(r) <= rank(&result));
+    sorted.insert(position, result);
+}
+
+fn remove_sorted(sorted: &mut Vec<Option<Duration>>, result: Option<Duration>) {
+    let position = sorted.partition_point(|r| rank(r) < rank(&result));
+    sorted.remove(position);
+}
+
+// The trimmed average of the last `size` results, kept sorted so each new
+// result only needs one insertion and one removal.
+#[derive(Debug, Clone)]
+struct RollingAverage {
+    size: usize,
+    window: VecDeque<Option<Duration>>,
+    sorted: Vec<Option<Duration>>,
+    best: Option<Duration>
+}
+
+impl RollingAverage {
+    fn new(size: usize) -> Self {
+        RollingAverage { size, window: VecDeque::with_capacity(size + 1), sorted: Vec::with_capacity(size + 1), best: None }
+    }
+
+    fn push(&mut self, result: Option<Duration>) {
+        self.window.push_back(result);
+        insert_sorted(&mut self.sorted, result);
+        if self.window.len() > self.size {
+            let oldest = self.window.pop_front().flatten();
+            remove_sorted(&mut self.sorted, oldest);
+        }
+
+        if let Some(current) = self.current() {
+            self.best = Some(self.best.map_or(current, |best| best.min(current)));
+        }
+    }
+
+    fn current(&self) -> Option<Duration> {
+        if self.window.len() < self.size {
+            return None
+        }
+        math::sorted_trimmed_mean(&self.sorted, 5)
+    }
+}
+
+// Aggregates over a session's solves, updated as each solve is added so the
+// stats panel doesn't recompute them over the whole session every frame.
+// They give the same values as the functions in `math`.
+#[derive(Debug, Clone)]
+pub struct SessionStats {
+    count: usize,
+    // Sum of completed results, for an exact `avg`.
+    result_total: Duration,
+    // Running mean and sum of squared deviations of completed results in
+    // seconds (Welford's method).
+    completed: usize,
+    mean_seconds: f64,
+    squared_deviations: f64,
+    // Every result, slowest last, for percentiles and trimmed means. Adding
+    // one shifts the slower results along, which is O(n) but a single move of
+    // 16-byte entries: around 10µs at 50,000 solves, once per solve. Reads
+    // then index it directly, which a tree would make O(log n) every frame.
+    sorted: Vec<Option<Duration>>,
+    phase_totals: [Duration; SPLIT_PHASES.len()],
+    phase_count: usize,
+    averages: [RollingAverage; 2]
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        SessionStats {
+            count: 0,
+            result_total: Duration::ZERO,
+            completed: 0,
+            mean_seconds: 0.0,
+            squared_deviations: 0.0,
+            sorted: Vec::new(),
+            phase_totals: [Duration::ZERO; SPLIT_PHASES.len()],
+            phase_count: 0,
+            averages: [RollingAverage::new(5), RollingAverage::new(12)]
+        }
+    }
+}
+
+impl SessionStats {
+    // Sorts the results once rather than inserting each in turn.
+    pub fn from_solves<'a>(solves: impl IntoIterator<Item = &'a Solve>) -> Self {
+        let mut stats = SessionStats::default();
+        for solve in solves.into_iter().filter(|solve| !solve.is_fewest_moves()) {
+            stats.add(solve);
+            stats.sorted.push(solve.result());
+        }
+        stats.sorted.sort_by_key(rank);
+        stats
+    }
+
+    pub fn push(&mut self, solve: &Solve) {
+        if solve.is_fewest_moves() {
+            return;
+        }
+        self.add(solve);
+        insert_sorted(&mut self.sorted, solve.result());
+    }
+
+    // Everything but the sorted results.
+    fn add(&mut self, solve: &Solve) {
+        let result = solve.result();
+        self.count += 1;
+        if let Some(time) = result {
+            self.result_total += time;
+            let seconds = time.as_secs_f64();
+            self.completed += 1;
+            let delta = seconds - self.mean_seconds;
+            self.mean_seconds += delta / self.completed as f64;
+            self.squared_deviations += delta * (seconds - self.mean_seconds);
+        }
+
+        let phases = solve.phases();
+        if phases.len() == SPLIT_PHASES.len() {
+            for (total, phase) in self.phase_totals.iter_mut().zip(phases) {
+                *total += phase;
+            }
+            self.phase_count += 1;
+        }
+
+        for average in &mut self.averages {
+            average.push(result);
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn avg(&self) -> Option<Duration> {
+        (self.completed > 0).then(|| self.result_total / self.completed as u32)
+    }
+
+    pub fn best(&self) -> Option<Duration> {
+        self.sorted.first().copied().flatten()
+    }
+
+    pub fn population_std(&self) -> Option<f64> {
+        (self.completed > 0).then(|| (self.squared_deviations / self.completed as f64).sqrt())
+    }
+
+    pub fn sample_std(&self) -> Option<f64> {
+        (self.completed > 1).then(|| (self.squared_deviations / (self.completed - 1) as f64).sqrt())
+    }
+
+    pub fn coefficient_of_variation(&self) -> Option<f64> {
+        Some(self.sample_std()? / self.mean_seconds)
+    }
+
+    pub fn median(&self) -> Option<Duration> {
+        math::sorted_percentile(&self.sorted, 0.5)
+    }
+
+    pub fn iqr(&self) -> Option<Duration> {
+        Some(math::sorted_percentile(&self.sorted, 0.75)? - math::sorted_percentile(&self.sorted, 0.25)?)
+    }
+
+    pub fn trimmed_mean(&self, percent: usize) -> Option<Duration> {
+        math::sorted_trimmed_mean(&self.sorted, percent)
+    }
+
+    pub fn rate_below(&self, target: Duration) -> Option<f64> {
+        if self.count == 0 {
+            return None
+        }
+        let below = self.sorted.partition_point(|result| rank(result) < target);
+        Some(below as f64 / self.count as f64)
+    }
+
+    // Current and best rolling averages; only ao5 and ao12 are kept.
+    pub fn ao(&self, size: usize) -> Option<Duration> {
+        self.average(size)?.current()
+    }
+
+    pub fn best_ao(&self, size: usize) -> Option<Duration> {
+        self.average(size)?.best
+    }
+
+    fn average(&self, size: usize) -> Option<&RollingAverage> {
+        self.averages.iter().find(|average| average.size == size)
+    }
+
+    pub fn phase_averages(&self) -> Option<[Duration; SPLIT_PHASES.len()]> {
+        (self.phase_count > 0).then(|| self.phase_totals.map(|total| total / self.phase_count as u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::Query;
+    use crate::report::best_average;
+    use crate::scramble::Puzzle;
+    use crate::solve::Penalty;
+    use crate::ui::{self, latest_times, segment_times_in_lines};
+    use chrono::Utc;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+    use std::time::Instant;
+
+    fn random_solves(count: usize, seed: u64) -> Vec<Solve> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..count)
+            .map(|_| {
+                let time = Duration::from_millis(rng.gen_range(8000..20000));
+                let (time, penalty) = match rng.gen_range(0..20) {
+                    0 => (None, Some(Penalty::DNF)),
+                    1 => (Some(time), Some(Penalty::Plus2)),
+                    _ => (Some(time), None),
+                };
+                Solve::build("R U".to_owned(), time, penalty).unwrap()
+            })
+            .collect()
+    }
+
+    // avg, best, median, IQR, 10% trimmed mean, sub-12 rate, and the
+    // current and best ao5 and ao12.
+    type Snapshot = ([Option<Duration>; 5], Option<f64>, [Option<Duration>; 4]);
+
+    // The naive path the stats panel used to take every frame.
+    fn naive(results: &[Option<Duration>]) -> Snapshot {
+        let times: Vec<_> = results.iter().flatten().copied().collect();
+        let last = |n: usize| (results.len() >= n).then(|| math::trimmed_avg(&results[results.len() - n..])).flatten();
+        (
+            [math::avg(&times), math::best(results), math::median(results), math::iqr(results), math::trimmed_mean(results, 10)],
+            math::rate_below(results, Duration::from_secs(12)),
+            [last(5), last(12), best_average(results, 5), best_average(results, 12)],
+        )
+    }
+
+    fn cached(stats: &SessionStats) -> Snapshot {
+        (
+            [stats.avg(), stats.best(), stats.median(), stats.iqr(), stats.trimmed_mean(10)],
+            stats.rate_below(Duration::from_secs(12)),
+            [stats.ao(5), stats.ao(12), stats.best_ao(5), stats.best_ao(12)],
+        )
+    }
+
+    #[test]
+    fn matches_math_as_solves_are_added() {
+        let solves = random_solves(200, 1);
+        let mut stats = SessionStats::default();
+        for (i, solve) in solves.iter().enumerate() {
+            stats.push(solve);
+            let results: Vec<_> = solves[..=i].iter().map(Solve::result).collect();
+
+            assert_eq!(cached(&stats), naive(&results), "after {} solves", i + 1);
+            assert_eq!(stats.count(), results.len());
+            for (cached, naive) in [
+                (stats.population_std(), math::population_std(&results)),
+                (stats.sample_std(), math::sample_std(&results)),
+                (stats.coefficient_of_variation(), math::coefficient_of_variation(&results)),
+            ] {
+                assert_eq!(cached.is_some(), naive.is_some());
+                assert!((cached.unwrap_or(0.0) - naive.unwrap_or(0.0)).abs() < 1e-9);
+            }
+        }
+
+        let built = SessionStats::from_solves(&solves);
+        assert_eq!(built.sorted, stats.sorted);
+        assert_eq!(cached(&built), cached(&stats));
+    }
+
+    #[test]
+    fn empty_stats() {
+        let stats = SessionStats::default();
+        assert_eq!(stats.avg(), None);
+        assert_eq!(stats.best(), None);
+        assert_eq!(stats.median(), None);
+        assert_eq!(stats.rate_below(Duration::from_secs(10)), None);
+        assert_eq!(stats.ao(5), None);
+        assert_eq!(stats.ao(100), None);
+        assert_eq!(stats.phase_averages(), None);
+    }
+
+    #[test]
+    fn avg_includes_penalties() {
+        let solves = [
+            Solve::build("R".to_owned(), Some(Duration::from_secs(10)), Some(Penalty::Plus2)).unwrap(),
+            Solve::build("R".to_owned(), Some(Duration::from_secs(14)), None).unwrap(),
+            Solve::build("R".to_owned(), None, Some(Penalty::DNF)).unwrap(),
+        ];
+        assert_eq!(SessionStats::from_solves(&solves).avg(), Some(Duration::from_secs(13)));
+    }
+
+    #[test]
+    fn fewest_moves_are_left_out() {
+        let solves = [
+            Solve::build("R".to_owned(), Some(Duration::from_secs(10)), None).unwrap(),
+            Solve::build_fmc("R".to_owned(), "R'".to_owned(), Some(1)),
+        ];
+        let stats = SessionStats::from_solves(&solves);
+        assert_eq!(stats.count(), 1);
+        assert_eq!(stats.median(), Some(Duration::from_secs(10)));
+
+        // Nor does it take a stored solution to tell them apart.
+        let unsolved: Solve = serde_json::from_str(r#"{"scramble":"R","time":null,"penalty":"DNF","fewest_moves":true}"#).unwrap();
+        let counted: Solve = serde_json::from_str(r#"{"scramble":"R","time":null,"penalty":null,"fewest_moves":true,"moves":28}"#).unwrap();
+        assert!(unsolved.solution().is_none() && counted.solution().is_none());
+        let stats = SessionStats::from_solves([&solves[0], &unsolved, &counted]);
+        assert_eq!(stats.count(), 1);
+        assert_eq!(stats.avg(), Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn phase_averages() {
+        let solves = [[3, 7, 5, 2], [5, 9, 7, 4]].map(|splits| {
+            let mut solve = Solve::build("R".to_owned(), Some(Duration::from_secs(splits.iter().sum())), None).unwrap();
+            let mut total = Duration::ZERO;
+            solve.set_splits(splits[..3].iter().map(|&s| { total += Duration::from_secs(s); total }).collect());
+            solve
+        });
+        let stats = SessionStats::from_solves(&solves);
+        assert_eq!(stats.phase_averages(), Some([4, 8, 6, 3].map(Duration::from_secs)));
+    }
+
+    // Times one frame of a filtered timer screen both ways over a large
+    // session: the stats and the times list, which the naive path filtered,
+    // formatted and split into lines in full.
+    // Run with `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn benchmark_against_naive() {
+        const FRAMES: u32 = 20;
+        const WIDTH: usize = 60;
+        const HEIGHT: usize = 20;
+        let solves = random_solves(50_000, 2);
+        let query = Query::parse("time<19").unwrap();
+        let filtered = || solves.iter().filter(|solve| query.matches(Puzzle::Cube3x3, solve, &Utc));
+
+        let start = Instant::now();
+        for _ in 0..FRAMES {
+            let shown: Vec<_> = filtered().collect();
+            let results: Vec<_> = shown.iter().map(|solve| solve.result()).collect();
+            let formatted: Vec<_> = shown.iter().map(|solve| ui::format_solve_result(solve)).collect();
+            std::hint::black_box((naive(&results), segment_times_in_lines(&formatted, WIDTH)));
+        }
+        let naive_frame = start.elapsed() / FRAMES;
+
+        let shown: Vec<_> = filtered().collect();
+        let mut stats = SessionStats::from_solves(shown[..shown.len() - 1].iter().copied());
+        let start = Instant::now();
+        stats.push(shown[shown.len() - 1]);
+        let push = start.elapsed();
+        let start = Instant::now();
+        for _ in 0..FRAMES {
+            let formatted: Vec<_> = latest_times(filtered(), WIDTH, HEIGHT).into_iter().map(|(_, time)| time).collect();
+            std::hint::black_box((cached(&stats), segment_times_in_lines(&formatted, WIDTH)));
+        }
+        let cached_frame = start.elapsed() / FRAMES;
+
+        // Well clear of timing noise, in the order of a hundredfold in release
+        // builds.
+        assert!(cached_frame * 20 < naive_frame, "cached {:?} against naive {:?} per frame", cached_frame, naive_frame);
+        assert!(push < naive_frame, "{:?} to add a solve against {:?} per naive frame", push, naive_frame);
+    }
+}
@@ -1,49 +1,249 @@
 use crate::session::Session;
+use crate::wca::RankIndex;
 use crate::Result;
 
-use std::{fs, io, path::PathBuf};
+use serde::Deserialize;
+use std::{cell::RefCell, env, fs, io, path::{Path, PathBuf}};
 
 pub trait Storage {
-    fn save_session(&self, session: &Session) -> Result<()>;
-    fn load_session(&self) -> Result<Session>;
+    fn save_sessions(&self, sessions: &[Session]) -> Result<()>;
+    fn load_sessions(&self) -> Result<Vec<Session>>;
+    // Writes a file for the user to read elsewhere, returning where it went.
+    fn export(&self, name: &str, contents: &str) -> Result<PathBuf>;
+    // Like `export`, to a path the user typed, relative to the current
+    // directory.
+    fn export_to(&self, path: &Path, contents: &str) -> Result<PathBuf>;
+    fn save_rank_index(&self, index: &RankIndex) -> Result<()>;
+    // None until a WCA export has been imported.
+    fn load_rank_index(&self) -> Result<Option<RankIndex>>;
 }
 
 const DIR: &str = ".cube";
 const FILE: &str = "sessions.json";
+const CONFIG_FILE: &str = "config.toml";
+const RANK_INDEX_FILE: &str = "wca-ranks.bin";
+
+// Files written before multiple sessions were supported hold a single session.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredSessions {
+    Many(Vec<Session>),
+    Single(Box<Session>)
+}
 
 pub struct FileSystemStorage {
     file_path: PathBuf
 }
 
+// The `.cube` directory in the local data directory, created if needed.
+fn data_dir() -> Result<PathBuf> {
+    let mut path = dirs::data_local_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not find local data directory"))?;
+    path.push(DIR);
+    fs::create_dir_all(&path)?;
+    Ok(path)
+}
+
+pub fn config_path() -> Result<PathBuf> {
+    Ok(data_dir()?.join(CONFIG_FILE))
+}
+
 impl FileSystemStorage {
     pub fn build() -> Result<Self> {
-        let file_path = Self::setup_file_path()?;
-        Ok(Self { file_path })
+        Ok(Self { file_path: data_dir()?.join(FILE) })
     }
 
-    fn setup_file_path() -> Result<PathBuf> {
-        let mut path = dirs::data_local_dir()
-            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not find local data directory"))?;
-        path.push(DIR);
-        path.push(FILE);
-        fs::create_dir_all(&path.parent().unwrap())?;
-        Ok(path)
+    // Whether a path leads to the sessions, config or rank index, following
+    // links and `..` so that no spelling of the path gets past.
+    fn is_data_file(&self, path: &Path) -> bool {
+        let canonical = |path: &Path| fs::canonicalize(path).ok().or_else(|| {
+            Some(fs::canonicalize(path.parent()?).ok()?.join(path.file_name()?))
+        });
+        let Some(target) = canonical(path) else {
+            return false;
+        };
+        [FILE, CONFIG_FILE, RANK_INDEX_FILE].iter()
+            .any(|name| canonical(&self.file_path.with_file_name(name)).as_ref() == Some(&target))
     }
 }
 
 impl Storage for FileSystemStorage {
-    fn save_session(&self, session: &Session) -> Result<()> {
-        let json = serde_json::to_string(session)?;
+    fn save_sessions(&self, sessions: &[Session]) -> Result<()> {
+        let json = serde_json::to_string(sessions)?;
         fs::write(&self.file_path, json)?;
         Ok(())
     }
 
-    fn load_session(&self) -> Result<Session> {
+    fn load_sessions(&self) -> Result<Vec<Session>> {
         if self.file_path.exists() {
             let json = fs::read_to_string(&self.file_path)?;
-            Ok(serde_json::from_str(&json)?)
+            match serde_json::from_str(&json)? {
+                StoredSessions::Many(sessions) => Ok(sessions),
+                StoredSessions::Single(session) => Ok(vec![*session]),
+            }
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    fn export(&self, name: &str, contents: &str) -> Result<PathBuf> {
+        let path = self.file_path.with_file_name(name);
+        fs::write(&path, contents)?;
+        Ok(path)
+    }
+
+    fn export_to(&self, path: &Path, contents: &str) -> Result<PathBuf> {
+        let path = env::current_dir()?.join(path);
+        if self.is_data_file(&path) {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("{} is one of the timer's own files", path.display())).into());
+        }
+        fs::write(&path, contents)?;
+        Ok(path)
+    }
+
+    fn save_rank_index(&self, index: &RankIndex) -> Result<()> {
+        fs::write(self.file_path.with_file_name(RANK_INDEX_FILE), index.to_bytes())?;
+        Ok(())
+    }
+
+    fn load_rank_index(&self) -> Result<Option<RankIndex>> {
+        let path = self.file_path.with_file_name(RANK_INDEX_FILE);
+        if path.exists() {
+            Ok(Some(RankIndex::from_bytes(&fs::read(path)?)?))
         } else {
-            Ok(Session::new())
+            Ok(None)
         }
     }
 }
+
+// Keeps everything for as long as the timer runs, without saving it. Nothing
+// is written, exports included.
+#[derive(Default)]
+pub struct MemoryStorage {
+    sessions: RefCell<String>,
+    rank_index: RefCell<Option<Vec<u8>>>
+}
+
+fn not_written() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "Nothing is written to disk with memory storage")
+}
+
+impl Storage for MemoryStorage {
+    fn save_sessions(&self, sessions: &[Session]) -> Result<()> {
+        *self.sessions.borrow_mut() = serde_json::to_string(sessions)?;
+        Ok(())
+    }
+
+    fn load_sessions(&self) -> Result<Vec<Session>> {
+        match self.sessions.borrow().as_str() {
+            "" => Ok(Vec::new()),
+            json => Ok(serde_json::from_str(json)?),
+        }
+    }
+
+    fn export(&self, _name: &str, _contents: &str) -> Result<PathBuf> {
+        Err(not_written().into())
+    }
+
+    fn export_to(&self, _path: &Path, _contents: &str) -> Result<PathBuf> {
+        Err(not_written().into())
+    }
+
+    fn save_rank_index(&self, index: &RankIndex) -> Result<()> {
+        *self.rank_index.borrow_mut() = Some(index.to_bytes());
+        Ok(())
+    }
+
+    fn load_rank_index(&self) -> Result<Option<RankIndex>> {
+        match self.rank_index.borrow().as_deref() {
+            Some(bytes) => Ok(Some(RankIndex::from_bytes(bytes)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+// An empty directory for one test, named after this process as well so
+// test runs going at the same time never share files.
+#[cfg(test)]
+pub fn test_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("cube-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage_with(name: &str, json: &str) -> FileSystemStorage {
+        let file_path = test_dir(name).join(FILE);
+        fs::write(&file_path, json).unwrap();
+        FileSystemStorage { file_path }
+    }
+
+    #[test]
+    fn load_single_session_file() {
+        let storage = storage_with("single-session", r#"{"solves":[]}"#);
+        assert_eq!(storage.load_sessions().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn save_and_load_sessions() {
+        let storage = storage_with("many-sessions", "[]");
+        storage.save_sessions(&[Session::new(Default::default()), Session::new(Default::default())]).unwrap();
+        assert_eq!(storage.load_sessions().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn export_next_to_sessions() {
+        let storage = storage_with("export-sessions", "[]");
+        let path = storage.export("report.md", "# Report").unwrap();
+        assert_eq!(path.parent(), storage.file_path.parent());
+        assert_eq!(fs::read_to_string(path).unwrap(), "# Report");
+    }
+
+    #[test]
+    fn export_to_path() {
+        let dir = test_dir("export-to");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        let storage = FileSystemStorage { file_path: dir.join(FILE) };
+        fs::write(dir.join(FILE), "[]").unwrap();
+
+        let path = storage.export_to(&dir.join("sub").join("solves.csv"), "a,b").unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "a,b");
+
+        for name in [FILE.to_string(), CONFIG_FILE.to_string(), format!("sub/../{}", RANK_INDEX_FILE)] {
+            assert!(storage.export_to(&dir.join(&name), "oops").is_err(), "{}", name);
+        }
+        assert_eq!(fs::read_to_string(dir.join(FILE)).unwrap(), "[]");
+    }
+
+    #[test]
+    fn save_and_load_rank_index() {
+        let dir = test_dir("rank-index");
+        let storage = FileSystemStorage { file_path: dir.join(FILE) };
+
+        assert!(storage.load_rank_index().unwrap().is_none());
+        storage.save_rank_index(&RankIndex::default()).unwrap();
+        assert_eq!(storage.load_rank_index().unwrap().unwrap().event_count(), 0);
+
+        fs::write(dir.join(RANK_INDEX_FILE), "{\"events\":{}}").unwrap();
+        assert!(storage.load_rank_index().is_err());
+    }
+
+    #[test]
+    fn memory_storage() {
+        let storage = MemoryStorage::default();
+        assert!(storage.load_sessions().unwrap().is_empty());
+        storage.save_sessions(&[Session::new(Default::default())]).unwrap();
+        assert_eq!(storage.load_sessions().unwrap().len(), 1);
+
+        assert!(storage.load_rank_index().unwrap().is_none());
+        storage.save_rank_index(&RankIndex::default()).unwrap();
+        assert!(storage.load_rank_index().unwrap().is_some());
+
+        assert!(storage.export("report.md", "# Report").is_err());
+        assert!(storage.export_to(Path::new("solves.csv"), "a,b").is_err());
+    }
+}
@@ -0,0 +1,153 @@
+use ratatui::{style::{Color, Style}, widgets::Block};
+
+pub const THEMES: [&str; 4] = ["dark", "light", "high-contrast", "solarized"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub background: Color,
+    pub text: Color,
+    pub border: Color,
+    // The timer text in each of its states.
+    pub idle: Color,
+    pub armed: Color,
+    pub inspecting: Color,
+    pub running: Color,
+    pub personal_best: Color,
+    pub plus_two: Color,
+    pub dnf: Color,
+    // Results compared with the session target and ao12.
+    pub faster: Color,
+    pub between: Color,
+    pub slower: Color
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            background: Color::Reset,
+            text: Color::Reset,
+            border: Color::Gray,
+            idle: Color::Reset,
+            armed: Color::Green,
+            inspecting: Color::Yellow,
+            running: Color::Cyan,
+            personal_best: Color::Magenta,
+            plus_two: Color::Yellow,
+            dnf: Color::Red,
+            faster: Color::Green,
+            between: Color::Yellow,
+            slower: Color::Red
+        }
+    }
+}
+
+impl Theme {
+    pub fn named(name: &str) -> Option<Theme> {
+        let dark = Theme::default();
+        Some(match name {
+            "dark" => dark,
+            "light" => {
+                // The background is set rather than left to the terminal,
+                // which is often dark. Yellow is hard to read on it.
+                let amber = Color::Rgb(0xaf, 0x5f, 0x00);
+                Theme {
+                    background: Color::Rgb(0xf5, 0xf5, 0xf5),
+                    text: Color::Black,
+                    border: Color::DarkGray,
+                    idle: Color::Black,
+                    inspecting: amber,
+                    running: Color::Blue,
+                    plus_two: amber,
+                    between: amber,
+                    ..dark
+                }
+            },
+            "high-contrast" => Theme {
+                background: Color::Black,
+                text: Color::White,
+                border: Color::White,
+                idle: Color::White,
+                armed: Color::LightGreen,
+                inspecting: Color::LightYellow,
+                running: Color::LightCyan,
+                personal_best: Color::LightMagenta,
+                plus_two: Color::LightYellow,
+                dnf: Color::LightRed,
+                faster: Color::LightGreen,
+                between: Color::LightYellow,
+                slower: Color::LightRed
+            },
+            "solarized" => {
+                let [green, yellow, red] = [Color::Rgb(0x85, 0x99, 0x00), Color::Rgb(0xb5, 0x89, 0x00), Color::Rgb(0xdc, 0x32, 0x2f)];
+                Theme {
+                    background: Color::Rgb(0x00, 0x2b, 0x36),
+                    text: Color::Rgb(0x83, 0x94, 0x96),
+                    border: Color::Rgb(0x58, 0x6e, 0x75),
+                    idle: Color::Rgb(0x93, 0xa1, 0xa1),
+                    armed: green,
+                    inspecting: yellow,
+                    running: Color::Rgb(0x26, 0x8b, 0xd2),
+                    personal_best: Color::Rgb(0xd3, 0x36, 0x82),
+                    plus_two: Color::Rgb(0xcb, 0x4b, 0x16),
+                    dnf: red,
+                    faster: green,
+                    between: yellow,
+                    slower: red
+                }
+            },
+            _ => return None,
+        })
+    }
+
+    // Sets a colour by its name in the config, false when there is no such
+    // colour.
+    pub fn set(&mut self, name: &str, colour: Color) -> bool {
+        *match name {
+            "background" => &mut self.background,
+            "text" => &mut self.text,
+            "border" => &mut self.border,
+            "idle" => &mut self.idle,
+            "armed" => &mut self.armed,
+            "inspecting" => &mut self.inspecting,
+            "running" => &mut self.running,
+            "personal_best" => &mut self.personal_best,
+            "plus_two" => &mut self.plus_two,
+            "dnf" => &mut self.dnf,
+            "faster" => &mut self.faster,
+            "between" => &mut self.between,
+            "slower" => &mut self.slower,
+            _ => return false,
+        } = colour;
+        true
+    }
+
+    pub fn style(&self) -> Style {
+        Style::new().fg(self.text).bg(self.background)
+    }
+
+    pub fn block(&self) -> Block<'static> {
+        Block::bordered().border_style(self.border)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_themes() {
+        for name in THEMES {
+            assert!(Theme::named(name).is_some(), "{}", name);
+        }
+        assert_eq!(Theme::named("dark"), Some(Theme::default()));
+        assert_eq!(Theme::named("neon"), None);
+    }
+
+    #[test]
+    fn set_colours() {
+        let mut theme = Theme::default();
+        assert!(theme.set("running", Color::White));
+        assert_eq!(theme.running, Color::White);
+        assert!(!theme.set("sparkle", Color::White));
+    }
+}
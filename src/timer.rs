@@ -1,4 +1,78 @@
+use crate::solve::Penalty;
+
+use std::fmt::Debug;
 use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+#[cfg(test)]
+use std::{cell::Cell, rc::Rc};
+
+// `now` times solves and `date` dates them.
+pub trait Clock: Debug {
+    fn now(&self) -> Instant;
+    fn date(&self) -> DateTime<Utc>;
+}
+
+#[derive(Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn date(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+// Only moves when advanced, starting at noon UTC on 2026-01-01. Clones share
+// the same time, so a test can keep one to drive a clock it handed to a
+// timer.
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub struct FakeClock {
+    start: Instant,
+    start_date: DateTime<Utc>,
+    offset: Rc<Cell<Duration>>
+}
+
+#[cfg(test)]
+impl FakeClock {
+    pub fn new() -> Self {
+        let start_date = "2026-01-01T12:00:00Z".parse().unwrap();
+        FakeClock { start: Instant::now(), start_date, offset: Rc::new(Cell::new(Duration::ZERO)) }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.offset.set(self.offset.get() + duration);
+    }
+}
+
+#[cfg(test)]
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        self.start + self.offset.get()
+    }
+
+    fn date(&self) -> DateTime<Utc> {
+        self.start_date + self.offset.get()
+    }
+}
+
+pub const INSPECTION: Duration = Duration::from_secs(15);
+const INSPECTION_DNF: Duration = Duration::from_secs(17);
+
+// Starting a solve after the 15 seconds of WCA inspection adds 2 seconds,
+// and after 17 seconds it is a DNF.
+pub fn inspection_penalty(inspected: Duration) -> Option<Penalty> {
+    if inspected > INSPECTION_DNF {
+        Some(Penalty::DNF)
+    } else if inspected > INSPECTION {
+        Some(Penalty::Plus2)
+    } else {
+        None
+    }
+}
 
 #[derive(Debug)]
 pub enum TimerState {
@@ -9,42 +83,75 @@ pub enum TimerState {
 
 #[derive(Debug)]
 pub struct Timer {
-    state: TimerState
+    state: TimerState,
+    splits: Vec<Duration>,
+    clock: Box<dyn Clock>
 }
 
 impl Timer {
-    pub fn new() -> Self {
-        Timer { state: TimerState::Idle }
+    pub fn new(clock: Box<dyn Clock>) -> Self {
+        Timer { state: TimerState::Idle, splits: Vec::new(), clock }
     }
 
+
     pub fn state(&self) -> &TimerState {
         &self.state
     }
 
+    // Elapsed time at each phase boundary marked while running.
+    pub fn splits(&self) -> &[Duration] {
+        &self.splits
+    }
+
+    pub fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    pub fn date(&self) -> DateTime<Utc> {
+        self.clock.date()
+    }
+
     pub fn start(&mut self) {
+        self.start_at(self.clock.now());
+    }
+
+    pub fn pause(&mut self) {
+        self.pause_at(self.clock.now());
+    }
+
+    // The `_at` variants take the instant the key was pressed, so the time
+    // does not depend on when the event gets handled.
+    pub fn split_at(&mut self, at: Instant) {
+        if let TimerState::Running { start } = self.state {
+            self.splits.push(at.saturating_duration_since(start));
+        }
+    }
+
+    pub fn start_at(&mut self, at: Instant) {
         self.state = match self.state {
-            TimerState::Idle => TimerState::Running { start: Instant::now() },
+            TimerState::Idle => TimerState::Running { start: at },
             TimerState::Paused { elapsed } => {
-                TimerState::Running { start: Instant::now() - elapsed }
+                TimerState::Running { start: at - elapsed }
             },
             TimerState::Running { .. }=> return,
         }
     }
 
-    pub fn pause(&mut self) {
+    pub fn pause_at(&mut self, at: Instant) {
         if let TimerState::Running { start } = self.state {
-            self.state = TimerState::Paused { elapsed: start.elapsed() }
+            self.state = TimerState::Paused { elapsed: at.saturating_duration_since(start) }
         }
     }
 
     pub fn reset(&mut self) {
         self.state = TimerState::Idle;
+        self.splits.clear();
     }
 
     pub fn elapsed(&self) -> Duration {
         match self.state {
             TimerState::Idle => Duration::ZERO,
-            TimerState::Running { start } => start.elapsed(),
+            TimerState::Running { start } => self.clock.now().saturating_duration_since(start),
             TimerState::Paused { elapsed } => elapsed,
         }
     }
@@ -54,96 +161,142 @@ impl Timer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::thread;
 
-    const SLEEP_TIME: Duration = Duration::from_millis(10);
+    const STEP: Duration = Duration::from_millis(10);
 
-    fn sleep_some_time() {
-        thread::sleep(SLEEP_TIME);
+    fn fake_timer() -> (Timer, FakeClock) {
+        let clock = FakeClock::new();
+        (Timer::new(Box::new(clock.clone())), clock)
     }
 
     #[test]
     fn new_timer() {
-        let timer = Timer::new();
+        let timer = Timer::new(Box::new(SystemClock));
         assert!(matches!(timer.state, TimerState::Idle));
     }
 
     #[test]
     fn start_from_idle() {
-        let mut timer = Timer::new();
+        let mut timer = Timer::new(Box::new(SystemClock));
         timer.start();
         assert!(matches!(timer.state, TimerState::Running { .. }))
     }
 
     #[test]
     fn start_from_paused() {
-        let mut timer = Timer::new();
+        let (mut timer, clock) = fake_timer();
         timer.start();
-        sleep_some_time();
+        clock.advance(STEP);
 
         timer.pause();
         let paused_time = timer.elapsed();
 
         timer.start();
-        sleep_some_time();
+        clock.advance(STEP);
 
         assert!(matches!(timer.state, TimerState::Running { .. }));
-        assert!(timer.elapsed() > paused_time);
+        assert_eq!(timer.elapsed(), paused_time + STEP);
     }
 
     #[test]
     fn elapsed() {
-        let mut timer = Timer::new();
+        let (mut timer, clock) = fake_timer();
         timer.start();
-        sleep_some_time();
+        clock.advance(STEP);
+        assert_eq!(timer.elapsed(), STEP);
 
-        let elapsed = timer.elapsed();
-        assert!(elapsed >= SLEEP_TIME);
-        assert!(elapsed < SLEEP_TIME * 2);
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(timer.elapsed(), STEP + Duration::from_secs(60));
     }
 
     #[test]
     fn pause() {
-        let mut timer = Timer::new();
+        let (mut timer, clock) = fake_timer();
         timer.start();
-        sleep_some_time();
+        clock.advance(STEP);
 
         timer.pause();
         let paused_time = timer.elapsed();
 
-        sleep_some_time();
+        clock.advance(STEP);
         assert_eq!(timer.elapsed(), paused_time);
+        assert_eq!(paused_time, STEP);
     }
 
     #[test]
     fn reset() {
-        let mut timer = Timer::new();
+        let (mut timer, clock) = fake_timer();
         timer.start();
-        sleep_some_time();
+        clock.advance(STEP);
 
         timer.reset();
         assert!(matches!(timer.state, TimerState::Idle));
         assert_eq!(timer.elapsed(), Duration::ZERO);
     }
 
+    #[test]
+    fn splits() {
+        let (mut timer, clock) = fake_timer();
+        timer.split_at(clock.now());
+        assert!(timer.splits().is_empty());
+
+        timer.start();
+        clock.advance(STEP);
+        timer.split_at(clock.now());
+        clock.advance(STEP);
+        timer.split_at(clock.now());
+        timer.pause();
+        timer.split_at(clock.now());
+
+        assert_eq!(timer.splits(), [STEP, STEP * 2]);
+        assert_eq!(timer.elapsed(), STEP * 2);
+
+        timer.reset();
+        assert!(timer.splits().is_empty());
+    }
+
+    #[test]
+    fn times_from_given_instants() {
+        let mut timer = Timer::new(Box::new(SystemClock));
+        let start = Instant::now();
+
+        timer.start_at(start);
+        timer.split_at(start + Duration::from_millis(1500));
+        timer.pause_at(start + Duration::from_millis(9870));
+        assert_eq!(timer.splits(), [Duration::from_millis(1500)]);
+        assert_eq!(timer.elapsed(), Duration::from_millis(9870));
+
+        timer.start_at(start + Duration::from_secs(20));
+        timer.pause_at(start + Duration::from_secs(21));
+        assert_eq!(timer.elapsed(), Duration::from_millis(10870));
+    }
+
     #[test]
     fn multiple_start_reset_cycles() {
-        let mut timer = Timer::new();
+        let (mut timer, clock) = fake_timer();
 
         timer.start();
-        sleep_some_time();
+        clock.advance(STEP);
         timer.reset();
 
         timer.start();
-        sleep_some_time();
+        clock.advance(STEP);
         timer.pause();
 
         let paused_time = timer.elapsed();
-        assert!(paused_time >= SLEEP_TIME);
+        assert_eq!(paused_time, STEP);
 
         timer.start();
-        sleep_some_time();
+        clock.advance(STEP);
+
+        assert_eq!(timer.elapsed(), paused_time + STEP);
+    }
 
-        assert!(timer.elapsed() > paused_time);
+    #[test]
+    fn inspection_penalties() {
+        assert_eq!(inspection_penalty(Duration::from_secs(15)), None);
+        assert_eq!(inspection_penalty(Duration::from_millis(15010)), Some(Penalty::Plus2));
+        assert_eq!(inspection_penalty(Duration::from_secs(17)), Some(Penalty::Plus2));
+        assert_eq!(inspection_penalty(Duration::from_millis(17010)), Some(Penalty::DNF));
     }
 }
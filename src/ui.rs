@@ -1,102 +1,938 @@
-use crate::app::App;
+use crate::app::{Analysis, App, MultiBldStage, View};
+use crate::command::{self, COMMANDS};
+use crate::compare::{self, Side};
+use crate::cube::format_alg;
+use crate::fmc;
+use crate::keys::{Action, KeyName};
 use crate::math;
+use crate::mbld;
+use crate::profile;
+use crate::report::{self, DaySummary, Forecast};
+use crate::scramble::{Format, Puzzle};
+use crate::solve::{Penalty, Solve, SPLIT_PHASES};
+use crate::stackmat::Status;
+use crate::theme::Theme;
+use crate::timer::{self, TimerState};
+use crate::wca::{self, Kind};
 
+use std::ops::Range;
 use std::time::Duration;
+use chrono::Local;
+use serde::Deserialize;
+use thiserror::Error;
 use ratatui::{
-    prelude::*, widgets::{Block, List, ListItem, Paragraph}, Frame
+    prelude::*, symbols::Marker, widgets::{Axis, Block, Chart, Clear, Dataset, GraphType, List, ListItem, Paragraph, Row, Table, Wrap}, Frame
 };
 use unicode_segmentation::UnicodeSegmentation;
 
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Precision {
+    Centiseconds,
+    Deciseconds,
+    Seconds,
+    Hidden
+}
+
+impl Precision {
+    pub fn next(&self) -> Precision {
+        match self {
+            Precision::Centiseconds => Precision::Deciseconds,
+            Precision::Deciseconds => Precision::Seconds,
+            Precision::Seconds => Precision::Hidden,
+            Precision::Hidden => Precision::Centiseconds,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LayoutPreset {
+    // Picks one of the others to suit the terminal's size.
+    Auto,
+    Wide,
+    Stacked,
+    Minimal
+}
+
+impl LayoutPreset {
+    pub fn next(&self) -> LayoutPreset {
+        match self {
+            LayoutPreset::Auto => LayoutPreset::Wide,
+            LayoutPreset::Wide => LayoutPreset::Stacked,
+            LayoutPreset::Stacked => LayoutPreset::Minimal,
+            LayoutPreset::Minimal => LayoutPreset::Auto,
+        }
+    }
+}
+
+// How the time is shown while the timer runs. Stopped times are always
+// shown in full.
+#[derive(Debug, Clone, Copy)]
+pub struct TimerDisplay {
+    pub precision: Precision,
+    pub large_digits: bool
+}
+
+impl Default for TimerDisplay {
+    fn default() -> Self {
+        TimerDisplay { precision: Precision::Centiseconds, large_digits: false }
+    }
+}
+
+impl TimerDisplay {
+    pub fn format(&self, time: Duration, running: bool) -> String {
+        let full = format_time(time);
+        if !running {
+            return full;
+        }
+
+        match self.precision {
+            Precision::Centiseconds => full,
+            Precision::Deciseconds => full[..full.len() - 1].to_string(),
+            Precision::Seconds => full[..full.len() - 3].to_string(),
+            Precision::Hidden => "solving".to_string(),
+        }
+    }
+}
+
 pub fn draw(frame: &mut Frame, app: &App) {
-    let main_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(vec![
-            Constraint::Length(3),
-            Constraint::Percentage(50),
-        ])
-        .split(frame.size());
+    frame.render_widget(Block::new().style(app.theme.style()), frame.size());
+    if let View::SolveDetail { index, analysis, tagging } = &app.view {
+        draw_solve_detail(frame, app, *index, analysis, *tagging);
+        return;
+    }
+    if let View::Report { summaries, forecast } = &app.view {
+        draw_report(frame, app, summaries, forecast.as_ref());
+        return;
+    }
+    if let View::Compare { sides, focus, editing } = &app.view {
+        draw_compare(frame, app, sides, *focus, *editing);
+        return;
+    }
+    if let View::Rankings = app.view {
+        draw_rankings(frame, app);
+        return;
+    }
+    if let View::Profile = app.view {
+        draw_profile(frame, app);
+        return;
+    }
 
-    let inner_layout = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(vec![
-            Constraint::Min(0),
-            Constraint::Length(36),
-            Constraint::Length(36),
-        ])
-        .split(main_layout[1]);
+    draw_timer(frame, app);
+    if let View::Help = app.view {
+        draw_help(frame, app);
+    }
+}
 
+fn draw_timer(frame: &mut Frame, app: &App) {
     let scramble = match &app.current_scramble {
-        Some(s) => s.clone(),
-        None => "".to_string()
+        Some(s) => label_scrambles(app.session().puzzle(), s.lines().collect()),
+        None => vec![]
+    };
+    let width = frame.size().width.saturating_sub(2).max(1) as usize;
+    let scramble_height: usize = scramble.iter()
+        .map(|line| line.graphemes(true).count().div_ceil(width).max(1))
+        .sum::<usize>()
+        .max(1);
+
+    // A filter naming a puzzle shows that puzzle's solves, in its format.
+    let puzzle = app.shown_puzzle();
+    let fewest_moves = puzzle == Puzzle::Cube3x3Fmc;
+    let multi_bld = puzzle == Puzzle::MultiBld;
+
+    let summary = app.stats();
+    let count = summary.count();
+    let ao12 = summary.ao(12);
+    let references: Vec<_> = [app.shown_target(), ao12].into_iter().flatten().collect();
+    let timed = puzzle.is_timed();
+    let best = summary.best();
+    let solve_colour = |solve: &Solve| solve_colour(solve, best, &references, &app.theme);
+
+    // Stats
+    let format_result = |result: Option<Duration>| result.map(format_time).unwrap_or_else(|| "DNF".to_string());
+
+    let event_stats = if multi_bld {
+        let best = app.filtered_solves()
+            .filter_map(|solve| Some((solve.multi_bld()?, solve.result()?)))
+            .min_by_key(|(result, time)| mbld::rank_key(result, *time));
+        match best {
+            Some((result, time)) => format!("best: {}/{} {} ({} points)", result.solved, result.attempted, format_time(time), result.points()),
+            None => "best: -".to_string(),
+        }
+    } else if fewest_moves {
+        let last: Vec<_> = app.filtered_solves().rev().take(3).map(Solve::moves).collect();
+        let format_moves = |mean: Option<f64>| mean.map(|m| format!("{:.2}", m)).unwrap_or_else(|| "DNF".to_string());
+        format!(
+            "best: {}, mo3: {}",
+            app.filtered_solves().filter_map(Solve::moves).min().map(|m| m.to_string()).unwrap_or_else(|| "-".to_string()),
+            if last.len() == 3 { format_moves(math::mean_moves(&last)) } else { "-".to_string() },
+        )
+    } else {
+        match puzzle.format() {
+            Format::AverageOf5 => format!(
+                "ao5: {}, ao12: {}",
+                if count >= 5 { format_result(summary.ao(5)) } else { "-".to_string() },
+                if count >= 12 { format_result(ao12) } else { "-".to_string() },
+            ),
+            Format::BestOf3 => {
+                let last: Vec<_> = app.filtered_solves().rev().take(3).map(Solve::result).collect();
+                format!(
+                    "best: {}, mo3: {}",
+                    if count == 0 { "-".to_string() } else { format_result(summary.best()) },
+                    if last.len() == 3 { format_result(math::mean(&last)) } else { "-".to_string() },
+                )
+            },
+        }
     };
-    let scramble = Paragraph::new(scramble)
-        .block(Block::bordered().title(" Scramble "));
-    frame.render_widget(scramble, main_layout[0]);
+
+    let mut stats = vec![Line::from(event_stats)];
+    if !fewest_moves && !multi_bld {
+        let format_seconds = |value: Option<f64>| value.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "-".to_string());
+        stats.insert(0, Line::from(format!(
+            "avg: {} (σ = {})",
+            if let Some(avg) = summary.avg() { format_time(avg) } else { "DNF".to_string() },
+            format_seconds(summary.population_std()),
+        )));
+        if count > 0 {
+            stats.extend([
+                format!("median: {}, IQR: {}", format_result(summary.median()), format_result(summary.iqr())),
+                format!(
+                    "sample σ: {}, CV: {}",
+                    format_seconds(summary.sample_std()),
+                    summary.coefficient_of_variation().map(|cv| format!("{:.1}%", cv * 100.0)).unwrap_or_else(|| "-".to_string()),
+                ),
+                format!("10% trimmed mean: {}", format_result(summary.trimmed_mean(10))),
+                format!(
+                    "solves: {}, best ao5: {}, ao12: {}",
+                    summary.count(),
+                    summary.best_ao(5).map(format_time).unwrap_or_else(|| "-".to_string()),
+                    summary.best_ao(12).map(format_time).unwrap_or_else(|| "-".to_string()),
+                ),
+            ].map(Line::from));
+        }
+    }
+
+    if let (Some(target), true) = (app.shown_target(), timed) {
+        let rate = summary.rate_below(target)
+            .map(|rate| format!("{:.1}%", rate * 100.0))
+            .unwrap_or_else(|| "-".to_string());
+        stats.push(Line::from(format!("sub-{}: {}", format_time(target), rate)));
+    }
+
+    for (name, avg) in SPLIT_PHASES.iter().zip(summary.phase_averages().into_iter().flatten()) {
+        stats.push(Line::from(format!("{} avg: {}", name, format_time(avg))));
+    }
+
+    let panels = panels(frame.size(), app.layout, scramble_height as u16 + 2, stats.len() as u16 + 2);
+    if let Some(area) = panels.scramble {
+        let scramble = Paragraph::new(scramble.into_iter().map(Line::from).collect::<Vec<_>>())
+            .wrap(Wrap { trim: true })
+            .block(app.theme.block().title(format!(
+                " Scramble ({}{}{}{}) ",
+                app.session().puzzle().name(),
+                if app.scramble_filter.is_active() { ", hard" } else { "" },
+                if app.split_mode { ", splits" } else { "" },
+                if app.manual_entry { ", manual" } else { "" },
+            )));
+        frame.render_widget(scramble, area);
+    }
 
     // timer
-    let timer = Paragraph::new(format_time(app.timer.elapsed()))
-        .block(Block::bordered());
-    frame.render_widget(timer, inner_layout[0]);
+    // Tiny terminals get the time alone, without even a border.
+    let timer_block = match panels.times {
+        Some(_) => app.theme.block(),
+        None => Block::new(),
+    };
+    let mut details = Vec::new();
+    if app.split_mode && !fewest_moves && !app.manual_entry {
+        let mut previous = Duration::ZERO;
+        for (name, &split) in SPLIT_PHASES.iter().zip(app.timer.splits()) {
+            details.push(Line::from(format!("{}: {}", name, format_time(split - previous))));
+            previous = split;
+        }
+        if let Some(name) = SPLIT_PHASES.get(app.timer.splits().len()) {
+            details.push(Line::from(format!("{}...", name)));
+        }
+    }
+    if let Some(message) = &app.message {
+        details.push(Line::from(message.as_str()));
+    }
+
+    // Typing in the palette, a filter or a target shows over any event.
+    let mut timer = if app.command_entry {
+        vec![
+            Line::from(format!(":{}", app.input)),
+            Line::from(command::completions(&app.input).join("  ")),
+        ]
+    } else if app.filter_entry {
+        vec![
+            Line::from(format!("Filter: {}", app.input)),
+            Line::from("e.g. tag:oh time<20 date>=2026-01-01"),
+        ]
+    } else if app.target_entry {
+        vec![
+            Line::from(format!("Target: {}", app.input)),
+            Line::from("Enter to set, empty to clear"),
+        ]
+    } else if fewest_moves {
+        vec![
+            Line::from(format_time(fmc::TIME_LIMIT.saturating_sub(app.timer.elapsed()))),
+            Line::from(format!("Solution: {}", app.input)),
+        ]
+    } else if multi_bld {
+        match app.multi_bld_stage {
+            MultiBldStage::Declaring => vec![Line::from(format!("Number of cubes: {}", app.input))],
+            MultiBldStage::Ready { cubes } => vec![
+                Line::from(format_time(app.timer.elapsed())),
+                Line::from(format!("Limit: {}", format_time(mbld::time_limit(cubes)))),
+            ],
+            MultiBldStage::Entering { time, .. } => vec![
+                Line::from(format_time(time)),
+                Line::from(format!("Solved/attempted: {}", app.input)),
+            ],
+        }
+    } else if app.manual_entry {
+        vec![Line::from(format!("Time: {}", app.input))]
+    } else {
+        let theme = &app.theme;
+        let running = matches!(app.timer.state(), TimerState::Running { .. });
+        // Inspection counts down whole seconds, then shows its penalty.
+        let (time, colour) = match app.inspection_elapsed() {
+            Some(inspected) => match timer::inspection_penalty(inspected) {
+                None => (timer::INSPECTION.saturating_sub(inspected).as_secs_f64().ceil().to_string(), theme.inspecting),
+                Some(Penalty::Plus2) => ("+2".to_string(), theme.plus_two),
+                Some(Penalty::DNF) => ("DNF".to_string(), theme.dnf),
+            },
+            None => {
+                let time = app.timer_display.format(app.timer.elapsed(), running);
+                match app.stackmat_status {
+                    _ if running => (time, theme.running),
+                    Some(Status::Ready) => (time, theme.armed),
+                    _ => (time, theme.idle),
+                }
+            },
+        };
+        let area = timer_block.inner(panels.timer);
+        let height = (area.height as usize).saturating_sub(details.len());
+        match large_text(&time, area.width as usize, height) {
+            Some(lines) if app.timer_display.large_digits => lines.into_iter().map(|line| Line::from(line).style(colour)).collect(),
+            _ => vec![Line::from(time).style(colour)],
+        }
+    };
+    // A just stopped time is coloured like its entry in the times list.
+    if timed && matches!(app.timer.state(), TimerState::Paused { .. }) && !app.target_entry && !app.manual_entry && !app.filter_entry && !app.command_entry {
+        if let Some(colour) = app.session().solves().last().and_then(solve_colour) {
+            timer = timer.into_iter().map(|line| line.style(colour)).collect();
+        }
+    }
+    timer.extend(details);
+    let timer = Paragraph::new(timer)
+        .block(timer_block);
+    frame.render_widget(timer, panels.timer);
 
     // times
-    let times: Vec<_> = app.session.solves()
+    let Some(times_area) = panels.times else {
+        return;
+    };
+    let width = times_area.width.saturating_sub(2).max(1) as usize;
+    let height = times_area.height.saturating_sub(2) as usize;
+    let (solves, formatted_times): (Vec<_>, Vec<_>) = latest_times(app.filtered_solves(), width, height).into_iter().unzip();
+    let lines = segment_times_in_lines(&formatted_times, width);
+    let time_list: Vec<_> = lines[lines.len().saturating_sub(height)..]
         .iter()
-        .filter_map(|solve| {
-            if let Some(time) = solve.time() {
-                Some(*time)
-            } else {
-                None
+        .map(|line| {
+            let mut spans = Vec::new();
+            for i in line.clone() {
+                let span = Span::raw(formatted_times[i].as_str());
+                spans.push(match solve_colour(solves[i]).filter(|_| timed) {
+                    Some(colour) => span.style(colour),
+                    None => span,
+                });
+                if i + 1 < solves.len() {
+                    spans.push(Span::raw(if i + 1 == line.end { "," } else { ", " }));
+                }
             }
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
-    let formatted_times: Vec<_> = times.iter().map(|time| format_time(*time)).collect();
-    let time_list: Vec<_> = segment_times_in_lines(&formatted_times, 35)
-        .iter()
-        .map(|time| ListItem::new(time.clone()))
+    let title = match &app.filter {
+        Some(query) => format!(" Times ({}) ", query.source()),
+        None => " Times ".to_string(),
+    };
+    let list = List::new(time_list)
+        .block(app.theme.block().title(title));
+    frame.render_widget(list, times_area);
+
+    if let Some(area) = panels.stats {
+        let stats = Paragraph::new(stats)
+                .block(app.theme.block().title(" Stats "));
+        frame.render_widget(stats, area);
+    }
+}
+
+// Where the timer screen's panels go. Only the timer is always shown.
+struct Panels {
+    scramble: Option<Rect>,
+    timer: Rect,
+    times: Option<Rect>,
+    stats: Option<Rect>
+}
+
+const PANEL_WIDTH: u16 = 36;
+// Terminals narrower than this stack the panels.
+const WIDE_WIDTH: u16 = 100;
+
+fn panels(area: Rect, preset: LayoutPreset, scramble_height: u16, stats_height: u16) -> Panels {
+    let preset = match preset {
+        LayoutPreset::Auto if area.width < 40 || area.height < 12 => LayoutPreset::Minimal,
+        LayoutPreset::Auto if area.width < WIDE_WIDTH => LayoutPreset::Stacked,
+        LayoutPreset::Auto => LayoutPreset::Wide,
+        preset => preset,
+    };
+
+    match preset {
+        LayoutPreset::Wide | LayoutPreset::Auto => {
+            let [scramble, rest] = Layout::vertical([Constraint::Length(scramble_height), Constraint::Fill(1)]).areas(area);
+            let [timer, times, stats] = Layout::horizontal([Constraint::Fill(1), Constraint::Length(PANEL_WIDTH), Constraint::Length(PANEL_WIDTH)]).areas(rest);
+            Panels { scramble: Some(scramble), timer, times: Some(times), stats: Some(stats) }
+        },
+        LayoutPreset::Stacked => {
+            let [scramble, timer, times, stats] = Layout::vertical([
+                Constraint::Length(scramble_height),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Length(stats_height),
+            ]).areas(area);
+            Panels { scramble: Some(scramble), timer, times: Some(times), stats: Some(stats) }
+        },
+        LayoutPreset::Minimal => Panels { scramble: None, timer: area, times: None, stats: None },
+    }
+}
+
+// The keys as configured and the palette's commands, over the timer screen.
+// Keys that typing and browsing rely on, which cannot be rebound.
+const FIXED_KEYS: [(&str, &str); 4] = [
+    ("←/→", "browse solves or sessions"),
+    ("tab", "switch compared side"),
+    ("esc", "close or cancel"),
+    ("enter", "confirm what was typed"),
+];
+
+const MULTI_BLD_KEYS: [(&str, &str); 2] = [
+    ("0-9", "cube count, then result"),
+    ("/", "between solved and attempted"),
+];
+
+fn draw_help(frame: &mut Frame, app: &App) {
+    const COLUMN_WIDTH: usize = 34;
+    let bound = |in_screen: bool| app.keymap.bindings().iter()
+        .filter(move |(action, _)| action.in_screen() == in_screen)
+        .map(|(action, key)| (KeyName(*key).to_string(), action.description()));
+    let sections = [
+        ("Keys", bound(false).collect::<Vec<_>>()),
+        ("In the detail, report and compare screens", bound(true).chain(FIXED_KEYS.map(|(key, description)| (key.to_string(), description))).collect()),
+        ("Multi-blind", MULTI_BLD_KEYS.map(|(key, description)| (key.to_string(), description)).to_vec()),
+    ];
+    let key_width = sections.iter().flat_map(|(_, keys)| keys).map(|(key, _)| key.chars().count()).max().unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for (title, keys) in sections {
+        let keys: Vec<_> = keys.iter()
+            .map(|(key, description)| format!("{:<key_width$}  {:<width$}", key, description, width = COLUMN_WIDTH - key_width - 2))
+            .collect();
+        // Keys go in two columns so the help fits on small terminals.
+        lines.push(Line::from(title).bold());
+        lines.extend(keys.chunks(2).map(|pair| Line::from(pair.concat().trim_end().to_string())));
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from(format!("Commands, after {}", KeyName(app.keymap.key(Action::Command)))).bold());
+    let usage_width = COMMANDS.iter().map(|(usage, _)| usage.len()).max().unwrap_or(0);
+    lines.extend(COMMANDS.iter().map(|(usage, description)| Line::from(format!("{:<usage_width$}  {}", usage, description))));
+
+    let area = frame.size();
+    let width = area.width.min(2 * COLUMN_WIDTH as u16 + 4);
+    let height = area.height.min(lines.len() as u16 + 2);
+    let popup = Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height);
+    let help = Paragraph::new(lines)
+        .style(app.theme.style())
+        .wrap(Wrap { trim: false })
+        .block(app.theme.block().title(" Help ").title_bottom(" esc back "));
+    frame.render_widget(Clear, popup);
+    frame.render_widget(help, popup);
+}
+
+fn draw_report(frame: &mut Frame, app: &App, summaries: &[DaySummary], forecast: Option<&Forecast>) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Percentage(50), Constraint::Min(0), Constraint::Length(1)])
+        .split(frame.size());
+
+    let widths = [12, 7, 10, 10, 10, 10, 11].map(Constraint::Length);
+    let table = Table::new(report::rows(summaries).into_iter().map(Row::new), widths)
+        .header(Row::new(report::header()).bold())
+        .block(app.theme.block().title(match &app.filter {
+            Some(query) => format!(" {} by day ({}) ", app.shown_puzzle().name(), query.source()),
+            None => format!(" {} by day ", app.shown_puzzle().name()),
+        }));
+    frame.render_widget(table, layout[0]);
+
+    match forecast {
+        Some(forecast) => draw_trend(frame, layout[1], &app.theme, forecast, app.shown_target()),
+        None => frame.render_widget(
+            Paragraph::new("A trend needs daily means on at least three days").block(app.theme.block().title(" Trend ")),
+            layout[1],
+        ),
+    }
+
+    let footer = app.message.clone()
+        .unwrap_or_else(|| format!("{}: export markdown and text, Esc: back", KeyName(app.keymap.key(Action::Export))));
+    frame.render_widget(Paragraph::new(footer), layout[2]);
+}
+
+const HISTOGRAM_BINS: usize = 20;
+
+fn draw_compare(frame: &mut Frame, app: &App, sides: &[Side; 2], focus: usize, editing: bool) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(8), Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)])
+        .split(frame.size());
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(layout[0]);
+
+    let results = sides.map(|side| {
+        let session = &app.sessions[side.session];
+        let solves = session.solves().iter().filter(|solve| app.matches_filter(session.puzzle(), solve));
+        compare::side_results(solves, side.range, &Local)
+    });
+    let names = sides.map(|side| {
+        let name = app.sessions[side.session].puzzle().name();
+        match side.range {
+            Some((from, to)) if from == to => format!("{} {}", name, from),
+            Some((from, to)) => format!("{} {}..{}", name, from, to),
+            None => name.to_string(),
+        }
+    });
+    let format_result = |result: Option<Duration>| result.map(format_time).unwrap_or_else(|| "-".to_string());
+
+    for (i, column) in columns.iter().enumerate() {
+        let stats = compare::side_stats(&results[i]);
+        let lines = vec![
+            Line::from(format!("solves: {}", stats.count)),
+            Line::from(format!("mean: {}", format_result(stats.mean))),
+            Line::from(format!("σ: {}", stats.std.map(|std| format!("{:.2}", std)).unwrap_or_else(|| "-".to_string()))),
+            Line::from(format!("best: {}", format_result(stats.best))),
+            Line::from(format!("best ao5: {}", format_result(stats.best_ao5))),
+            Line::from(format!("best ao12: {}", format_result(stats.best_ao12))),
+        ];
+        let mut block = app.theme.block().title(format!(" {} ", names[i]));
+        if i == focus {
+            block = block.border_style(Style::new().fg(app.theme.border).bold());
+        }
+        frame.render_widget(Paragraph::new(lines).block(block), *column);
+    }
+
+    let significance = match compare::significance(&results[0], &results[1]) {
+        Some(test) => format!(
+            "Welch's t = {:.2}, df = {:.1}, p = {:.3}: {}",
+            test.t,
+            test.degrees_of_freedom,
+            test.p,
+            if test.p < 0.05 { "significant at 5%" } else { "not significant at 5%" },
+        ),
+        None => "Not enough completed solves to test the difference".to_string(),
+    };
+    frame.render_widget(Paragraph::new(significance), layout[1]);
+
+    draw_histograms(frame, layout[2], &app.theme, &names, &results);
+
+    let footer = if editing {
+        format!("Date range (YYYY-MM-DD..YYYY-MM-DD, empty for all): {}", app.input)
+    } else {
+        format!("Tab: switch side, Left/Right: session, {}: date range, Esc: back", KeyName(app.keymap.key(Action::DateRange)))
+    };
+    let footer = app.message.clone().unwrap_or(footer);
+    frame.render_widget(Paragraph::new(footer), layout[3]);
+}
+
+// Overlaid outlines of each side's distribution, as a share of its solves
+// so sides of different sizes can be compared.
+fn draw_histograms(frame: &mut Frame, area: Rect, theme: &Theme, names: &[String; 2], results: &[Vec<Option<Duration>>; 2]) {
+    let times = results.each_ref().map(|results| results.iter().flatten().map(Duration::as_secs_f64).collect::<Vec<_>>());
+    let all = times.iter().flatten();
+    let (min, max) = all.fold((f64::MAX, f64::MIN), |(min, max), &time| (min.min(time), max.max(time)));
+    if min > max {
+        frame.render_widget(Paragraph::new("No completed solves").block(theme.block().title(" Distribution ")), area);
+        return;
+    }
+
+    let width = ((max - min) / HISTOGRAM_BINS as f64).max(0.01);
+    let outlines = times.each_ref().map(|times| {
+        math::histogram(times, min, width, HISTOGRAM_BINS + 1)
+            .iter()
+            .enumerate()
+            .flat_map(|(bin, &count)| {
+                let share = count as f64 / times.len().max(1) as f64;
+                let start = min + bin as f64 * width;
+                [(start, share), (start + width, share)]
+            })
+            .collect::<Vec<_>>()
+    });
+    let top = outlines.iter().flatten().map(|p| p.1).fold(0.0, f64::max).max(0.01);
+
+    let datasets = vec![
+        Dataset::default().name(names[0].clone()).marker(Marker::Braille).graph_type(GraphType::Line).cyan().data(&outlines[0]),
+        Dataset::default().name(names[1].clone()).marker(Marker::Braille).graph_type(GraphType::Line).magenta().data(&outlines[1]),
+    ];
+    let end = min + (HISTOGRAM_BINS + 1) as f64 * width;
+    let chart = Chart::new(datasets)
+        .block(theme.block().title(" Distribution "))
+        .x_axis(Axis::default()
+            .title("s")
+            .bounds([min, end])
+            .labels(vec![Span::raw(format!("{:.1}", min)), Span::raw(format!("{:.1}", end))]))
+        .y_axis(Axis::default()
+            .bounds([0.0, top])
+            .labels(vec![Span::raw("0%"), Span::raw(format!("{:.0}%", top * 100.0))]));
+    frame.render_widget(chart, area);
+}
+
+// Daily means with the fitted trend and its confidence band, extended up to
+// the forecast date when there is one.
+fn draw_trend(frame: &mut Frame, area: Rect, theme: &Theme, forecast: &Forecast, goal: Option<Duration>) {
+    let last_x = forecast.points.last().map(|p| p.0).unwrap_or(0.0);
+    let end_x = match forecast.date {
+        Some(date) => ((date - forecast.first_day).num_days() as f64).clamp(last_x, last_x * 3.0 + 7.0),
+        None => last_x,
+    };
+    let steps = 100;
+    let xs: Vec<_> = (0..=steps).map(|i| end_x * i as f64 / steps as f64).collect();
+    let trend: Vec<_> = xs.iter().map(|&x| (x, forecast.regression.predict(x))).collect();
+    let lower: Vec<_> = xs.iter().map(|&x| (x, forecast.regression.band(x).0)).collect();
+    let upper: Vec<_> = xs.iter().map(|&x| (x, forecast.regression.band(x).1)).collect();
+
+    let ys = forecast.points.iter().map(|p| p.1).chain(lower.iter().chain(&upper).map(|p| p.1)).chain(goal.map(|g| g.as_secs_f64()));
+    let (min_y, max_y) = ys.fold((f64::MAX, f64::MIN), |(min, max), y| (min.min(y), max.max(y)));
+    let min_y = min_y.max(0.0).floor();
+    let max_y = max_y.ceil().max(min_y + 1.0);
+
+    let datasets = vec![
+        Dataset::default().name("daily mean").marker(Marker::Dot).graph_type(GraphType::Scatter).data(&forecast.points),
+        Dataset::default().name("trend").marker(Marker::Braille).graph_type(GraphType::Line).cyan().data(&trend),
+        Dataset::default().marker(Marker::Braille).graph_type(GraphType::Line).dark_gray().data(&lower),
+        Dataset::default().marker(Marker::Braille).graph_type(GraphType::Line).dark_gray().data(&upper),
+    ];
+
+    let trend_name = match forecast.regression.trend {
+        math::Trend::Linear => "linear",
+        math::Trend::PowerLaw => "power law",
+    };
+    let title = match (goal, forecast.date) {
+        (Some(goal), Some(date)) => format!(" Trend ({}): {} projected for {} ", trend_name, format_time(goal), date),
+        (Some(goal), None) => format!(" Trend ({}): not heading for {} ", trend_name, format_time(goal)),
+        (None, _) => format!(" Trend ({}): set a target for a forecast ", trend_name),
+    };
+
+    let chart = Chart::new(datasets)
+        .block(theme.block().title(title))
+        .x_axis(Axis::default()
+            .title("days")
+            .bounds([0.0, end_x.max(1.0)])
+            .labels(vec![Span::raw("0"), Span::raw(format!("{:.0}", end_x))]))
+        .y_axis(Axis::default()
+            .title("s")
+            .bounds([min_y, max_y])
+            .labels(vec![Span::raw(format!("{:.0}", min_y)), Span::raw(format!("{:.0}", max_y))]));
+    frame.render_widget(chart, area);
+}
+
+fn draw_rankings(frame: &mut Frame, app: &App) {
+    let puzzle = app.session().puzzle();
+    let title = format!(" {} world rankings{} ", puzzle.name(), app.country.as_ref().map(|c| format!(" ({})", c)).unwrap_or_default());
+    let block = app.theme.block().title(title).title_bottom(" esc back ");
+
+    let (index, event) = match (&app.rank_index, wca::event_id(puzzle)) {
+        (None, _) => {
+            let text = app.message.as_deref().unwrap_or("No WCA rankings yet. Unpack the WCA results export and run with --wca-import <dir>");
+            frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: true }).block(block), frame.size());
+            return;
+        },
+        (_, None) => {
+            frame.render_widget(Paragraph::new(format!("{} is not a WCA event", puzzle.name())).block(block), frame.size());
+            return;
+        },
+        (Some(index), Some(event)) => (index, event),
+    };
+
+    let format_position = |position: Option<wca::Position>| match position {
+        Some(position) => format!("{} of {} (top {:.1}%)", position.rank, position.of, position.rank as f64 * 100.0 / position.of.max(1) as f64),
+        None => "-".to_string(),
+    };
+    let rows: Vec<_> = [Kind::Single, Kind::Average].into_iter()
+        .zip(wca::personal_bests(app.session()))
+        .map(|(kind, best)| {
+            let ranks = best.and_then(|best| index.ranks(event, kind, best, app.country.as_deref()));
+            Row::new([
+                format!("{:?}", kind),
+                best.map(|best| wca::format_result(puzzle, kind, best)).unwrap_or_else(|| "-".to_string()),
+                format_position(ranks.map(|ranks| ranks.world)),
+                format_position(ranks.and_then(|ranks| ranks.continent)),
+                format_position(ranks.and_then(|ranks| ranks.country)),
+            ])
+        })
         .collect();
 
-    let list = List::new(time_list)
-        .block(Block::bordered().title(" Times "));
-    frame.render_widget(list, inner_layout[1]);
-        
-    // Stats
-    let stats = format!(
-        "avg: {} (σ = {})",
-        if let Some(avg) = math::avg(&times) { format_time(avg) } else { "DNF".to_string() },
-        if let Some(std) = math::std(&times) { format_time(std) } else { "-1".to_string() },
-    );
-    let stats = Paragraph::new(stats)
-            .block(Block::bordered().title(" Stats "));
-    frame.render_widget(stats, inner_layout[2]);
+    let table = Table::new(rows, [Constraint::Length(8), Constraint::Length(16), Constraint::Fill(1), Constraint::Fill(1), Constraint::Fill(1)])
+        .header(Row::new(["", "PB", "World", "Continent", "Country"]).bold())
+        .block(block);
+    frame.render_widget(table, frame.size());
+}
+
+fn draw_profile(frame: &mut Frame, app: &App) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Min(0), Constraint::Length(4)])
+        .split(frame.size());
+
+    let records = app.reference_records();
+    let profile = profile::profile(&app.sessions, &records, app.rank_index.as_ref());
+    let dash = || "-".to_string();
+    let rows: Vec<_> = profile.events.iter()
+        .map(|event| {
+            let [single, average] = [Kind::Single, Kind::Average]
+                .map(|kind| event.bests[kind as usize].map(|best| wca::format_result(event.puzzle, kind, best)).unwrap_or_else(dash));
+            let [single_rank, average_rank] = event.ranks.map(|rank| rank.map(|rank| rank.rank.to_string()).unwrap_or_else(dash));
+            Row::new([
+                event.puzzle.name().to_string(),
+                single,
+                average,
+                event.kinch.map(|kinch| format!("{:.2}", kinch)).unwrap_or_else(dash),
+                single_rank,
+                average_rank,
+            ])
+        })
+        .collect();
+    let widths = [Constraint::Length(10), Constraint::Fill(1), Constraint::Fill(1), Constraint::Length(8), Constraint::Length(12), Constraint::Length(12)];
+    let table = Table::new(rows, widths)
+        .header(Row::new(["Event", "Single", "Average", "Kinch", "Rank single", "Rank average"]).bold())
+        .block(app.theme.block().title(" Profile ").title_bottom(" esc back "));
+    frame.render_widget(table, layout[0]);
+
+    let summary = vec![
+        Line::from(match profile.kinch {
+            Some(kinch) => format!("Kinch score: {:.2}", kinch),
+            None => "Kinch score: needs records, import the WCA export or set them in the config".to_string(),
+        }),
+        Line::from(match profile.sum_of_ranks {
+            [Some(single), Some(average)] => format!("Sum of ranks: {} single, {} average", single, average),
+            _ => "Sum of ranks: needs an imported WCA export".to_string(),
+        }),
+    ];
+    let summary = match &app.message {
+        Some(message) => vec![Line::from(message.as_str())],
+        None => summary,
+    };
+    frame.render_widget(Paragraph::new(summary).block(app.theme.block()), layout[1]);
+}
+
+fn draw_solve_detail(frame: &mut Frame, app: &App, index: usize, analysis: &Analysis, tagging: bool) {
+    let solve = &app.session().solves()[index];
+
+    let mut lines: Vec<_> = label_scrambles(app.session().puzzle(), solve.scrambles())
+        .into_iter()
+        .map(|scramble| Line::from(format!("Scramble: {}", scramble)))
+        .collect();
+    lines.push(Line::from(format!("Result: {}", format_solve_result(solve))));
+    for (name, phase) in SPLIT_PHASES.iter().zip(solve.phases()) {
+        lines.push(Line::from(format!("  {}: {}", name, format_time(phase))));
+    }
+    if let Some(cross_length) = solve.cross_length() {
+        lines.push(Line::from(format!("Cross length: {}", cross_length)));
+    }
+    if let Some(solution) = solve.solution() {
+        lines.push(Line::from(format!("Solution: {}", solution)));
+    }
+    if tagging {
+        lines.push(Line::from(format!("Tags: {}", app.input)));
+    } else if !solve.tags().is_empty() {
+        lines.push(Line::from(format!("Tags: {}", solve.tags().join(", "))));
+    }
+    lines.push(Line::from(""));
+
+    match analysis {
+        Analysis::Done(analysis) => {
+            lines.push(Line::from("Optimal cross:"));
+            for (colour, moves) in &analysis.crosses {
+                lines.push(Line::from(format!("  {:<7} {} ({})", colour.colour_name(), format_alg(moves), moves.len())));
+            }
+
+            let (colour, moves) = &analysis.xcross;
+            lines.push(Line::from(""));
+            lines.push(Line::from(format!("Optimal X-cross ({}): {} ({})", colour.colour_name(), format_alg(moves), moves.len())));
+        },
+        Analysis::Pending(_) => lines.push(Line::from("Working out the optimal cross...")),
+        Analysis::Unavailable => lines.push(Line::from("Cross analysis is only available for 3x3 scrambles")),
+    }
+
+    let title = format!(" Solve {}/{} ", index + 1, app.session().solves().len());
+    let detail = Paragraph::new(lines)
+        .wrap(Wrap { trim: true })
+        .block(app.theme.block().title(title).title_bottom(if tagging {
+            " enter save, esc cancel ".to_string()
+        } else {
+            format!(" ←/→ browse, {} tags, esc back ", KeyName(app.keymap.key(Action::Tags)))
+        }));
+    frame.render_widget(detail, frame.size());
+}
+
+fn label_scrambles(puzzle: Puzzle, scrambles: Vec<&str>) -> Vec<String> {
+    match puzzle.relay() {
+        Some(puzzles) => puzzles.iter()
+            .zip(scrambles)
+            .map(|(puzzle, scramble)| format!("{}: {}", puzzle.name(), scramble))
+            .collect(),
+        None if puzzle == Puzzle::MultiBld => scrambles.into_iter()
+            .enumerate()
+            .map(|(i, scramble)| format!("{}: {}", i + 1, scramble))
+            .collect(),
+        None => scrambles.into_iter().map(String::from).collect(),
+    }
+}
+
+// The faster colour (green) when faster than every reference time (the
+// target and current ao12), slower (red) when slower than all of them, and
+// between (yellow) otherwise.
+fn result_colour(result: Option<Duration>, references: &[Duration], theme: &Theme) -> Option<Color> {
+    if references.is_empty() {
+        return None;
+    }
+
+    let time = match result {
+        Some(time) => time,
+        None => return Some(theme.slower),
+    };
+    let faster = references.iter().filter(|&&reference| time < reference).count();
+    Some(match faster {
+        0 => theme.slower,
+        n if n == references.len() => theme.faster,
+        _ => theme.between,
+    })
+}
+
+// DNFs, personal bests and +2s stand out from the colour of their result.
+fn solve_colour(solve: &Solve, best: Option<Duration>, references: &[Duration], theme: &Theme) -> Option<Color> {
+    match (solve.result(), solve.penalty()) {
+        (None, _) => Some(theme.dnf),
+        (result, _) if result == best => Some(theme.personal_best),
+        (_, Some(Penalty::Plus2)) => Some(theme.plus_two),
+        (result, _) => result_colour(result, references, theme),
+    }
+}
+
+pub fn format_solve_result(solve: &Solve) -> String {
+    if let Some(moves) = solve.moves() {
+        return moves.to_string();
+    }
+    if let Some(result) = solve.multi_bld() {
+        return match solve.result() {
+            Some(time) => format!("{}/{} {}", result.solved, result.attempted, format_time(time)),
+            None => format!("DNF ({}/{})", result.solved, result.attempted),
+        };
+    }
+
+    match (solve.result(), solve.penalty()) {
+        (None, _) => "DNF".to_string(),
+        (Some(time), Some(Penalty::Plus2)) => format!("{}+", format_time(time)),
+        (Some(time), _) => format_time(time),
+    }
+}
+
+const GLYPH_HEIGHT: usize = 5;
+
+fn glyph(c: char) -> Option<[&'static str; GLYPH_HEIGHT]> {
+    Some(match c {
+        '0' => ["███", "█ █", "█ █", "█ █", "███"],
+        '1' => ["  █", "  █", "  █", "  █", "  █"],
+        '2' => ["███", "  █", "███", "█  ", "███"],
+        '3' => ["███", "  █", "███", "  █", "███"],
+        '4' => ["█ █", "█ █", "███", "  █", "  █"],
+        '5' => ["███", "█  ", "███", "  █", "███"],
+        '6' => ["███", "█  ", "███", "█ █", "███"],
+        '7' => ["███", "  █", "  █", "  █", "  █"],
+        '8' => ["███", "█ █", "███", "█ █", "███"],
+        '9' => ["███", "█ █", "███", "  █", "███"],
+        ':' => [" ", "█", " ", "█", " "],
+        '.' => [" ", " ", " ", " ", "█"],
+        _ => return None,
+    })
+}
+
+// Renders text in block digits, scaled up as far as the width and height
+// allow. None when a character has no glyph or the text does not fit.
+fn large_text(text: &str, width: usize, height: usize) -> Option<Vec<String>> {
+    let glyphs = text.chars().map(glyph).collect::<Option<Vec<_>>>()?;
+    let text_width = glyphs.iter().map(|g| g[0].chars().count() + 1).sum::<usize>().saturating_sub(1);
+    let scale = (width / text_width.max(1)).min(height / GLYPH_HEIGHT);
+    if scale == 0 {
+        return None;
+    }
+
+    let lines = (0..GLYPH_HEIGHT).flat_map(|row| {
+        let line: String = glyphs.iter()
+            .map(|g| g[row].chars().flat_map(|c| std::iter::repeat_n(c, scale)).collect::<String>())
+            .collect::<Vec<_>>()
+            .join(&" ".repeat(scale));
+        std::iter::repeat_n(line, scale)
+    });
+    Some(lines.collect())
+}
+
+// The latest solves, oldest first, with their results formatted. Only
+// enough to fill more than `height` lines of `width` are formatted, as the
+// list shows the newest times.
+pub(crate) fn latest_times<'a>(solves: impl DoubleEndedIterator<Item = &'a Solve>, width: usize, height: usize) -> Vec<(&'a Solve, String)> {
+    let mut filled = 0;
+    let mut times: Vec<_> = solves.rev()
+        .map(|solve| (solve, format_solve_result(solve)))
+        .take_while(|(_, time)| {
+            let more = filled <= width * (height + 1);
+            filled += (time.graphemes(true).count() + 2).min(width);
+            more
+        })
+        .collect();
+    times.reverse();
+    times
 }
 
-fn segment_times_in_lines(times: &[String], max_width: usize) -> Vec<String> {
+// The indices of the times on each line, with ", " between them. A time
+// wider than a line gets one to itself.
+pub(crate) fn segment_times_in_lines(times: &[String], max_width: usize) -> Vec<Range<usize>> {
     let mut lines = Vec::new();
-    let mut current_line = String::new();
+    let mut start = 0;
+    let mut width = 0;
 
     for (i, time) in times.iter().enumerate() {
-        let separator = if i == times.len() - 1 { "" } else { ", " };
-        let potential_addition = format!("{}{}", time, separator);
-        
-        if current_line.graphemes(true).count() + potential_addition.graphemes(true).count() <= max_width {
-            current_line.push_str(&potential_addition);
-        } else {
-            if !current_line.is_empty() {
-                lines.push(current_line.trim_end().to_string());
-            }
-            current_line = potential_addition.to_string();
+        let separator = if i == times.len() - 1 { 0 } else { 2 };
+        let addition = time.graphemes(true).count() + separator;
+        if width + addition > max_width && i > start {
+            lines.push(start..i);
+            start = i;
+            width = 0;
         }
+        width += addition;
     }
 
-    if !current_line.is_empty() {
-        lines.push(current_line.trim_end().to_string());
+    if start < times.len() {
+        lines.push(start..times.len());
     }
 
     lines
 }
 
-fn format_time(time: Duration) -> String {
+pub fn format_time(time: Duration) -> String {
     let total_seconds = time.as_secs();
     let hours = total_seconds / 3600;
     let minutes = (total_seconds % 3600) / 60;
@@ -110,6 +946,70 @@ fn format_time(time: Duration) -> String {
     }
 }
 
+#[derive(Error, Debug, PartialEq)]
+pub enum TimeError {
+    #[error("Invalid time: {0}")]
+    InvalidTime(String),
+    #[error("A +2 result must be at least 2 seconds")]
+    PenaltyExceedsTime
+}
+
+// Inverse of `format_time`. Fields above seconds are optional, as is the
+// fraction, which may have up to three digits.
+pub fn parse_time(input: &str) -> std::result::Result<Duration, TimeError> {
+    let invalid = || TimeError::InvalidTime(input.to_string());
+
+    let fields: Vec<&str> = input.trim().split(':').collect();
+    if fields.len() > 3 {
+        return Err(invalid());
+    }
+
+    let last = fields[fields.len() - 1];
+    let (whole, fraction) = match last.split_once('.') {
+        Some((_, "")) => return Err(invalid()),
+        Some(split) => split,
+        None => (last, ""),
+    };
+    if fraction.len() > 3 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let millis = format!("{:0<3}", fraction).parse::<u64>().map_err(|_| invalid())?;
+
+    let mut seconds: u64 = 0;
+    for (i, field) in fields[..fields.len() - 1].iter().copied().chain([whole]).enumerate() {
+        if field.is_empty() || !field.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let value: u64 = field.parse().map_err(|_| invalid())?;
+        if i > 0 && value >= 60 {
+            return Err(invalid());
+        }
+        seconds = seconds.checked_mul(60).and_then(|s| s.checked_add(value)).ok_or_else(invalid)?;
+    }
+
+    Duration::from_secs(seconds).checked_add(Duration::from_millis(millis)).ok_or_else(invalid)
+}
+
+// Inverse of `format_solve_result` for timed solves: `DNF`, or a time with
+// a trailing `+` when it includes a +2 penalty. Returns the raw time and
+// penalty to build a `Solve` with.
+pub fn parse_solve_result(input: &str) -> std::result::Result<(Option<Duration>, Option<Penalty>), TimeError> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("dnf") {
+        return Ok((None, Some(Penalty::DNF)));
+    }
+
+    match input.strip_suffix('+') {
+        Some(result) => {
+            let time = parse_time(result)?
+                .checked_sub(Duration::from_secs(2))
+                .ok_or(TimeError::PenaltyExceedsTime)?;
+            Ok((Some(time), Some(Penalty::Plus2)))
+        },
+        None => Ok((Some(parse_time(input)?), None))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -141,6 +1041,151 @@ mod tests {
         assert_eq!(format_time(Duration::from_secs(86400)), String::from("24:00:00.00"));
     }
 
+    #[test]
+    fn parse_times() {
+        assert_eq!(parse_time("12.34"), Ok(Duration::from_millis(12340)));
+        assert_eq!(parse_time("0.5"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_time("9"), Ok(Duration::from_secs(9)));
+        assert_eq!(parse_time("8.123"), Ok(Duration::from_millis(8123)));
+        assert_eq!(parse_time(" 75.00 "), Ok(Duration::from_secs(75)));
+        assert_eq!(parse_time("1:02.50"), Ok(Duration::from_millis(62500)));
+        assert_eq!(parse_time("10:00"), Ok(Duration::from_secs(600)));
+        assert_eq!(parse_time("1:00:00.00"), Ok(Duration::from_secs(3600)));
+        assert_eq!(parse_time("24:00:00.00"), Ok(Duration::from_secs(86400)));
+    }
+
+    #[test]
+    fn parse_invalid_times() {
+        for input in ["", ".", "12.", "abc", "1.2.3", "12.3456", "-1.00", "1:60.00", "1:60:00.00", ":12.34", "1::00", "1:2:3:4.00", "12.3a", "1 2.00"] {
+            assert!(parse_time(input).is_err(), "{:?} should not parse", input);
+        }
+    }
+
+    #[test]
+    fn parse_overflowing_times() {
+        for input in ["999999999999999999:00", "18446744073709551616", "9999999999999999:59:59"] {
+            assert_eq!(parse_time(input), Err(TimeError::InvalidTime(input.to_string())));
+        }
+        assert_eq!(parse_time("18446744073709551615"), Ok(Duration::from_secs(u64::MAX)));
+    }
+
+    #[test]
+    fn parse_time_inverts_format_time() {
+        for millis in [0, 10, 990, 7480, 45000, 59990, 60000, 62500, 599990, 3600000, 3723450, 86400000] {
+            let time = Duration::from_millis(millis);
+            assert_eq!(parse_time(&format_time(time)), Ok(time));
+        }
+    }
+
+    #[test]
+    fn parse_solve_results() {
+        assert!(matches!(parse_solve_result("DNF"), Ok((None, Some(Penalty::DNF)))));
+        assert!(matches!(parse_solve_result("dnf"), Ok((None, Some(Penalty::DNF)))));
+        assert!(matches!(parse_solve_result("12.34"), Ok((Some(t), None)) if t == Duration::from_millis(12340)));
+        assert!(matches!(parse_solve_result("12.34+"), Ok((Some(t), Some(Penalty::Plus2))) if t == Duration::from_millis(10340)));
+        assert!(matches!(parse_solve_result("1:02.50+"), Ok((Some(t), Some(Penalty::Plus2))) if t == Duration::from_millis(60500)));
+        assert_eq!(parse_solve_result("1.50+").unwrap_err(), TimeError::PenaltyExceedsTime);
+        assert!(parse_solve_result("DNF+").is_err());
+        assert!(parse_solve_result("12.34++").is_err());
+        assert!(parse_solve_result("+").is_err());
+    }
+
+    #[test]
+    fn parse_solve_result_inverts_format_solve_result() {
+        let solves = [
+            Solve::build("R".to_owned(), Some(Duration::from_millis(12340)), None).unwrap(),
+            Solve::build("R".to_owned(), Some(Duration::from_millis(61500)), Some(Penalty::Plus2)).unwrap(),
+            Solve::build("R".to_owned(), None, Some(Penalty::DNF)).unwrap(),
+        ];
+        for solve in solves {
+            let (time, penalty) = parse_solve_result(&format_solve_result(&solve)).unwrap();
+            let parsed = Solve::build("R".to_owned(), time, penalty).unwrap();
+            assert_eq!(parsed.result(), solve.result());
+            assert_eq!(parsed.time(), solve.time());
+        }
+    }
+
+    #[test]
+    fn running_time_precision() {
+        let time = Duration::from_millis(83456);
+        let display = |precision| TimerDisplay { precision, large_digits: false };
+
+        assert_eq!(display(Precision::Centiseconds).format(time, true), "1:23.45");
+        assert_eq!(display(Precision::Deciseconds).format(time, true), "1:23.4");
+        assert_eq!(display(Precision::Seconds).format(time, true), "1:23");
+        assert_eq!(display(Precision::Hidden).format(time, true), "solving");
+        assert_eq!(display(Precision::Hidden).format(time, false), "1:23.45");
+    }
+
+    #[test]
+    fn large_text_scales_to_fit() {
+        let lines = large_text("1.2", 9, 5).unwrap();
+        assert_eq!(lines, vec!["  █   ███", "  █     █", "  █   ███", "  █   █  ", "  █ █ ███"]);
+
+        let lines = large_text("1.2", 20, 12).unwrap();
+        assert_eq!(lines.len(), 10);
+        assert_eq!(lines[0], "    ██      ██████");
+        assert_eq!(lines[0], lines[1]);
+
+        assert_eq!(large_text("1.2", 8, 5), None);
+        assert_eq!(large_text("1.2", 9, 4), None);
+        assert_eq!(large_text("solving", 100, 100), None);
+    }
+
+    #[test]
+    fn colour_relative_to_references() {
+        let target = Duration::from_secs(15);
+        let ao12 = Duration::from_secs(17);
+        let theme = Theme::default();
+
+        assert_eq!(result_colour(Some(Duration::from_secs(14)), &[], &theme), None);
+        assert_eq!(result_colour(Some(Duration::from_secs(14)), &[target], &theme), Some(Color::Green));
+        assert_eq!(result_colour(Some(Duration::from_secs(15)), &[target], &theme), Some(Color::Red));
+        assert_eq!(result_colour(Some(Duration::from_secs(14)), &[target, ao12], &theme), Some(Color::Green));
+        assert_eq!(result_colour(Some(Duration::from_secs(16)), &[target, ao12], &theme), Some(Color::Yellow));
+        assert_eq!(result_colour(Some(Duration::from_secs(18)), &[target, ao12], &theme), Some(Color::Red));
+        assert_eq!(result_colour(None, &[ao12], &theme), Some(Color::Red));
+    }
+
+    #[test]
+    fn solve_colours() {
+        let theme = Theme::named("solarized").unwrap();
+        let solve = |millis: Option<u64>, penalty| Solve::build("R".to_owned(), millis.map(Duration::from_millis), penalty).unwrap();
+        let best = Some(Duration::from_millis(9000));
+        let target = [Duration::from_secs(12)];
+
+        assert_eq!(solve_colour(&solve(None, Some(Penalty::DNF)), best, &target, &theme), Some(theme.dnf));
+        assert_eq!(solve_colour(&solve(Some(9000), None), best, &target, &theme), Some(theme.personal_best));
+        assert_eq!(solve_colour(&solve(Some(7000), Some(Penalty::Plus2)), best, &target, &theme), Some(theme.personal_best));
+        assert_eq!(solve_colour(&solve(Some(9000), Some(Penalty::Plus2)), best, &target, &theme), Some(theme.plus_two));
+        assert_eq!(solve_colour(&solve(Some(10000), None), best, &target, &theme), Some(theme.faster));
+        assert_eq!(solve_colour(&solve(Some(10000), None), best, &[], &theme), None);
+    }
+
+    #[test]
+    fn panels_fit_the_terminal() {
+        let shown = |panels: &Panels| [panels.scramble.is_some(), panels.times.is_some(), panels.stats.is_some()];
+
+        let wide = panels(Rect::new(0, 0, 120, 30), LayoutPreset::Auto, 3, 10);
+        assert_eq!(shown(&wide), [true; 3]);
+        assert_eq!(wide.timer, Rect::new(0, 3, 120 - 2 * PANEL_WIDTH, 27));
+        assert_eq!(wide.stats, Some(Rect::new(120 - PANEL_WIDTH, 3, PANEL_WIDTH, 27)));
+
+        let stacked = panels(Rect::new(0, 0, 60, 40), LayoutPreset::Auto, 4, 10);
+        assert_eq!(stacked.scramble, Some(Rect::new(0, 0, 60, 4)));
+        assert_eq!(stacked.timer, Rect::new(0, 4, 60, 13));
+        assert_eq!(stacked.times, Some(Rect::new(0, 17, 60, 13)));
+        assert_eq!(stacked.stats, Some(Rect::new(0, 30, 60, 10)));
+
+        let tiny = panels(Rect::new(0, 0, 30, 8), LayoutPreset::Auto, 3, 10);
+        assert_eq!(shown(&tiny), [false; 3]);
+        assert_eq!(tiny.timer, Rect::new(0, 0, 30, 8));
+
+        assert_eq!(shown(&panels(Rect::new(0, 0, 120, 30), LayoutPreset::Minimal, 3, 10)), [false; 3]);
+        assert_eq!(panels(Rect::new(0, 0, 120, 30), LayoutPreset::Stacked, 3, 10).timer.width, 120);
+        assert_eq!(shown(&panels(Rect::new(0, 0, 30, 8), LayoutPreset::Wide, 3, 10)), [true; 3]);
+    }
+
     #[test]
     fn times_segmentation() {
         let times = vec![
@@ -149,7 +1194,23 @@ mod tests {
             "1:45:54.99".to_string(),
         ];
 
-        assert_eq!(segment_times_in_lines(&times, 25),vec!["1.54, 1:43.50, 1:45:54.99"]);
-        assert_eq!(segment_times_in_lines(&times, 15), vec!["1.54, 1:43.50,", "1:45:54.99"]);
+        assert_eq!(segment_times_in_lines(&times, 25), vec![0..3]);
+        assert_eq!(segment_times_in_lines(&times, 15), vec![0..2, 2..3]);
+        assert_eq!(segment_times_in_lines(&times, 5), vec![0..1, 1..2, 2..3]);
+        assert!(segment_times_in_lines(&[], 5).is_empty());
+    }
+
+    #[test]
+    fn only_the_latest_times_are_formatted() {
+        let solves: Vec<_> = (1..=100)
+            .map(|s| Solve::build("R".to_owned(), Some(Duration::from_secs(s)), None).unwrap())
+            .collect();
+        let times = latest_times(solves.iter(), 13, 2);
+        let formatted: Vec<_> = times.iter().map(|(_, time)| time.as_str()).collect();
+        assert_eq!(formatted, ["1:36.00", "1:37.00", "1:38.00", "1:39.00", "1:40.00"]);
+
+        let lines = segment_times_in_lines(&formatted.iter().map(|time| time.to_string()).collect::<Vec<_>>(), 13);
+        assert!(lines.len() > 2);
+        assert_eq!(latest_times(solves[..2].iter(), 13, 2).len(), 2);
     }
 }
@@ -0,0 +1,590 @@
+use crate::math;
+use crate::mbld::{self, MultiBldResult};
+use crate::scramble::{Format, Puzzle};
+use crate::session::Session;
+use crate::ui::format_time;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum WcaError {
+    #[error("No {0} file in the WCA export")]
+    MissingFile(&'static str),
+    #[error("{0} has no {1} column")]
+    MissingColumn(PathBuf, &'static str),
+    #[error("Invalid row {1} in {0}")]
+    InvalidRow(PathBuf, usize),
+    #[error("The imported rankings are damaged, import the WCA export again")]
+    CorruptIndex,
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Single,
+    Average
+}
+
+pub fn event_id(puzzle: Puzzle) -> Option<&'static str> {
+    match puzzle {
+        Puzzle::Cube2x2 => Some("222"),
+        Puzzle::Cube3x3 => Some("333"),
+        Puzzle::Cube4x4 => Some("444"),
+        Puzzle::Cube5x5 => Some("555"),
+        Puzzle::Cube6x6 => Some("666"),
+        Puzzle::Cube7x7 => Some("777"),
+        Puzzle::Cube3x3Bld => Some("333bf"),
+        Puzzle::Cube4x4Bld => Some("444bf"),
+        Puzzle::Cube5x5Bld => Some("555bf"),
+        Puzzle::Cube3x3Fmc => Some("333fm"),
+        Puzzle::MultiBld => Some("333mbf"),
+        Puzzle::Relay2To4 | Puzzle::Relay2To7 => None,
+    }
+}
+
+// Results as the WCA stores them, where lower is always better: times in
+// centiseconds, single fewest moves results in moves and their means in
+// hundredths of a move.
+pub fn encode_time(time: Duration) -> u32 {
+    (time.as_millis() / 10) as u32
+}
+
+pub fn encode_moves(moves: f64) -> u32 {
+    (moves * 100.0).round() as u32
+}
+
+// Multi-blind packs 99 minus the points, the time in seconds and the cubes
+// missed into one number.
+pub fn encode_multi_bld(result: &MultiBldResult, time: Duration) -> u32 {
+    let points = (99 - result.points()).clamp(0, 99) as u32;
+    points * 10_000_000 + time.as_secs().min(99_999) as u32 * 100 + result.missed().min(99)
+}
+
+// Points, seconds and cubes missed.
+pub fn decode_multi_bld(result: u32) -> (i64, u64, u32) {
+    (99 - (result / 10_000_000) as i64, result as u64 / 100 % 100_000, result % 100)
+}
+
+pub fn format_result(puzzle: Puzzle, kind: Kind, result: u32) -> String {
+    match (puzzle, kind) {
+        (Puzzle::Cube3x3Fmc, Kind::Single) => result.to_string(),
+        (Puzzle::Cube3x3Fmc, Kind::Average) => format!("{:.2}", result as f64 / 100.0),
+        (Puzzle::MultiBld, _) => {
+            let (points, seconds, missed) = decode_multi_bld(result);
+            let solved = points + missed as i64;
+            format!("{}/{} {}", solved, solved + missed as i64, format_time(Duration::from_secs(seconds)))
+        },
+        _ => format_time(Duration::from_millis(result as u64 * 10)),
+    }
+}
+
+// The session's best single and best average (or mean of 3) in the WCA's
+// encoding. Multi-blind has no averages.
+pub fn personal_bests(session: &Session) -> [Option<u32>; 2] {
+    let solves = session.solves();
+    match session.puzzle() {
+        Puzzle::Cube3x3Fmc => {
+            let moves: Vec<_> = solves.iter().map(|solve| solve.moves()).collect();
+            [
+                moves.iter().flatten().min().copied(),
+                moves.windows(3).filter_map(math::mean_moves).map(encode_moves).min(),
+            ]
+        },
+        Puzzle::MultiBld => {
+            let best = solves.iter()
+                .filter_map(|solve| Some((solve.multi_bld()?, solve.result()?)))
+                .min_by_key(|(result, time)| mbld::rank_key(result, *time));
+            [best.map(|(result, time)| encode_multi_bld(result, time)), None]
+        },
+        puzzle => {
+            let stats = session.stats();
+            let average = match puzzle.format() {
+                Format::AverageOf5 => stats.best_ao(5),
+                Format::BestOf3 => {
+                    let results: Vec<_> = solves.iter().map(|solve| solve.result()).collect();
+                    results.windows(3).filter_map(math::mean).min()
+                },
+            };
+            [stats.best().map(encode_time), average.map(encode_time)]
+        },
+    }
+}
+
+// Reference results per event, such as world records, in the WCA's
+// encoding.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Records {
+    events: BTreeMap<String, [Option<u32>; 2]>
+}
+
+impl Records {
+    pub fn get(&self, event: &str, kind: Kind) -> Option<u32> {
+        self.events.get(event)?[kind as usize]
+    }
+
+    pub fn set(&mut self, event: &str, kind: Kind, result: u32) {
+        self.events.entry(event.to_string()).or_default()[kind as usize] = Some(result);
+    }
+
+    // These records, with any set in `overrides` taking their place.
+    pub fn with_overrides(mut self, overrides: &Records) -> Records {
+        for (event, results) in &overrides.events {
+            let entry = self.events.entry(event.clone()).or_default();
+            for (result, over) in entry.iter_mut().zip(results) {
+                *result = over.or(*result);
+            }
+        }
+        self
+    }
+}
+
+// Every ranked result of one kind for an event, fastest first, with the
+// position of its person's country in `RankIndex::countries`. The continent
+// and country rankings are rebuilt from these rather than stored.
+#[derive(Debug, Default)]
+struct Ranking {
+    results: Vec<(u32, Option<u16>)>,
+    continents: BTreeMap<String, Vec<u32>>,
+    countries: BTreeMap<String, Vec<u32>>
+}
+
+impl Ranking {
+    fn group(&mut self, countries: &[(&String, &String)]) {
+        self.results.sort_unstable();
+        self.continents.clear();
+        self.countries.clear();
+        for &(result, country) in &self.results {
+            if let Some(&(country, continent)) = country.and_then(|country| countries.get(country as usize)) {
+                self.countries.entry(country.clone()).or_default().push(result);
+                self.continents.entry(continent.clone()).or_default().push(result);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub rank: usize,
+    pub of: usize
+}
+
+// A result ranks after every strictly better result, so ties share a rank.
+fn position<T>(results: &[T], result: u32, value: impl Fn(&T) -> u32) -> Position {
+    Position { rank: results.partition_point(|r| value(r) < result) + 1, of: results.len() }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ranks {
+    pub world: Position,
+    pub continent: Option<Position>,
+    pub country: Option<Position>
+}
+
+// Rankings built from the WCA results export, keeping only what is needed
+// to place a result.
+#[derive(Debug, Default)]
+pub struct RankIndex {
+    // Country id to continent id, with ISO codes as aliases of the id.
+    countries: BTreeMap<String, String>,
+    aliases: HashMap<String, String>,
+    events: BTreeMap<String, [Ranking; 2]>
+}
+
+impl RankIndex {
+    // Reads the Countries, Persons, RanksSingle and RanksAverage files from
+    // an unpacked export. Both the older camel case and the newer snake case
+    // file and column names are accepted.
+    pub fn import(dir: &Path) -> Result<RankIndex, WcaError> {
+        let mut index = RankIndex::default();
+
+        let countries = Table::open(dir, "countries")?;
+        let (id, continent, iso2) = (countries.column("id")?, countries.column("continentid")?, countries.column("iso2").ok());
+        for (line, row) in countries.rows() {
+            let (Some(id), Some(continent)) = (row.get(id), row.get(continent)) else {
+                return Err(WcaError::InvalidRow(countries.path.clone(), line));
+            };
+            if let Some(iso2) = iso2.and_then(|iso2| row.get(iso2)) {
+                index.aliases.insert(iso2.to_lowercase(), id.to_string());
+            }
+            index.countries.insert(id.to_string(), continent.to_string());
+        }
+
+        let country_positions: HashMap<_, _> = index.countries.keys().enumerate().map(|(i, id)| (id.clone(), i as u16)).collect();
+        let persons = Table::open(dir, "persons")?;
+        let id = persons.column("wcaid").or_else(|_| persons.column("id"))?;
+        let country = persons.column("countryid")?;
+        let subid = persons.column("subid").ok();
+        let mut person_countries = HashMap::new();
+        for (line, row) in persons.rows() {
+            // Older rows of a person who changed country have a higher subid.
+            if subid.is_some_and(|subid| row.get(subid).is_some_and(|subid| *subid != "1")) {
+                continue;
+            }
+            let (Some(id), Some(country)) = (row.get(id), row.get(country)) else {
+                return Err(WcaError::InvalidRow(persons.path.clone(), line));
+            };
+            person_countries.insert(id.to_string(), country_positions.get(*country).copied());
+        }
+
+        for (kind, name) in [(Kind::Single, "rankssingle"), (Kind::Average, "ranksaverage")] {
+            let ranks = Table::open(dir, name)?;
+            let (person, event, best) = (ranks.column("personid")?, ranks.column("eventid")?, ranks.column("best")?);
+            for (line, row) in ranks.rows() {
+                let invalid = || WcaError::InvalidRow(ranks.path.clone(), line);
+                let (Some(person), Some(event)) = (row.get(person), row.get(event)) else {
+                    return Err(invalid());
+                };
+                let best: i64 = row.get(best).and_then(|best| best.parse().ok()).ok_or_else(invalid)?;
+                if best <= 0 {
+                    continue;
+                }
+
+                let country = person_countries.get(*person).copied().flatten();
+                index.events.entry(event.to_string()).or_default()[kind as usize].results.push((best as u32, country));
+            }
+        }
+
+        index.group();
+        Ok(index)
+    }
+
+    fn group(&mut self) {
+        let countries: Vec<_> = self.countries.iter().collect();
+        for ranking in self.events.values_mut().flatten() {
+            ranking.group(&countries);
+        }
+    }
+
+    // The countries, their aliases, then each event's results as the gap
+    // from the result before and the country's position plus one, or zero
+    // when unknown. All numbers are LEB128 varints and strings are prefixed
+    // by their length.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = INDEX_MAGIC.to_vec();
+        write_varint(&mut bytes, self.countries.len() as u64);
+        for (id, continent) in &self.countries {
+            write_str(&mut bytes, id);
+            write_str(&mut bytes, continent);
+        }
+        write_varint(&mut bytes, self.aliases.len() as u64);
+        for (alias, id) in &self.aliases {
+            write_str(&mut bytes, alias);
+            write_str(&mut bytes, id);
+        }
+        write_varint(&mut bytes, self.events.len() as u64);
+        for (event, rankings) in &self.events {
+            write_str(&mut bytes, event);
+            for ranking in rankings {
+                write_varint(&mut bytes, ranking.results.len() as u64);
+                let mut previous = 0;
+                for &(result, country) in &ranking.results {
+                    write_varint(&mut bytes, (result - previous) as u64);
+                    write_varint(&mut bytes, country.map_or(0, |country| country as u64 + 1));
+                    previous = result;
+                }
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<RankIndex, WcaError> {
+        let mut reader = Reader { bytes: bytes.strip_prefix(INDEX_MAGIC).ok_or(WcaError::CorruptIndex)? };
+        let mut index = RankIndex::default();
+        for _ in 0..reader.len()? {
+            index.countries.insert(reader.string()?, reader.string()?);
+        }
+        for _ in 0..reader.len()? {
+            index.aliases.insert(reader.string()?, reader.string()?);
+        }
+        for _ in 0..reader.len()? {
+            let event = reader.string()?;
+            let mut rankings: [Ranking; 2] = Default::default();
+            for ranking in &mut rankings {
+                let mut result = 0_u32;
+                for _ in 0..reader.len()? {
+                    result = result.checked_add(reader.varint()?.try_into().map_err(|_| WcaError::CorruptIndex)?).ok_or(WcaError::CorruptIndex)?;
+                    let country = match reader.varint()? {
+                        0 => None,
+                        country if country as usize <= index.countries.len() => Some(country as u16 - 1),
+                        _ => return Err(WcaError::CorruptIndex),
+                    };
+                    ranking.results.push((result, country));
+                }
+            }
+            index.events.insert(event, rankings);
+        }
+        if !reader.bytes.is_empty() {
+            return Err(WcaError::CorruptIndex);
+        }
+
+        index.group();
+        Ok(index)
+    }
+
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    // The world records, being the best ranked results.
+    pub fn records(&self) -> Records {
+        let mut records = Records::default();
+        for (event, rankings) in &self.events {
+            for (kind, ranking) in [Kind::Single, Kind::Average].into_iter().zip(rankings) {
+                if let Some(&(best, _)) = ranking.results.first() {
+                    records.set(event, kind, best);
+                }
+            }
+        }
+        records
+    }
+
+    // The country is a WCA country id such as `United Kingdom`, or its ISO
+    // code, matched case-insensitively.
+    pub fn country_id(&self, country: &str) -> Option<&str> {
+        let country = country.trim();
+        self.countries.keys()
+            .find(|id| id.eq_ignore_ascii_case(country))
+            .or_else(|| self.aliases.get(&country.to_lowercase()))
+            .map(String::as_str)
+    }
+
+    pub fn ranks(&self, event: &str, kind: Kind, result: u32, country: Option<&str>) -> Option<Ranks> {
+        let ranking = &self.events.get(event)?[kind as usize];
+        if ranking.results.is_empty() {
+            return None;
+        }
+
+        let country = country.and_then(|country| self.country_id(country));
+        let continent = country.and_then(|country| self.countries.get(country));
+        Some(Ranks {
+            world: position(&ranking.results, result, |&(r, _)| r),
+            continent: continent.and_then(|continent| ranking.continents.get(continent)).map(|results| position(results, result, |&r| r)),
+            country: country.and_then(|country| ranking.countries.get(country)).map(|results| position(results, result, |&r| r)),
+        })
+    }
+}
+
+const INDEX_MAGIC: &[u8] = b"cube-ranks 1\n";
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn write_str(bytes: &mut Vec<u8>, text: &str) {
+    write_varint(bytes, text.len() as u64);
+    bytes.extend(text.as_bytes());
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8]
+}
+
+impl Reader<'_> {
+    fn varint(&mut self) -> Result<u64, WcaError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = self.bytes.split_first().ok_or(WcaError::CorruptIndex)?;
+            self.bytes = rest;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(WcaError::CorruptIndex)
+    }
+
+    // A count or length, which can be no more than the bytes left, so a
+    // damaged file cannot ask for a huge allocation.
+    fn len(&mut self) -> Result<usize, WcaError> {
+        match self.varint()? {
+            len if len <= self.bytes.len() as u64 => Ok(len as usize),
+            _ => Err(WcaError::CorruptIndex),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, WcaError> {
+        let len = self.len()?;
+        let (text, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        String::from_utf8(text.to_vec()).map_err(|_| WcaError::CorruptIndex)
+    }
+}
+
+// A tab separated export file with a header row.
+struct Table {
+    path: PathBuf,
+    header: Vec<String>,
+    contents: String
+}
+
+// `RanksSingle`, `ranks_single` and `continentId` all normalise alike.
+fn normalise(name: &str) -> String {
+    name.chars().filter(|c| *c != '_').flat_map(char::to_lowercase).collect()
+}
+
+impl Table {
+    fn open(dir: &Path, name: &'static str) -> Result<Table, WcaError> {
+        let file_name = format!("{}.tsv", name);
+        let path = fs::read_dir(dir)?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .find(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(|n| {
+                let n = normalise(n);
+                n == file_name || n.ends_with(&format!("export{}", file_name))
+            }))
+            .ok_or(WcaError::MissingFile(name))?;
+
+        let contents = fs::read_to_string(&path)?;
+        let header = contents.lines().next().unwrap_or("").split('\t').map(|column| normalise(column.trim())).collect();
+        Ok(Table { path, header, contents })
+    }
+
+    fn column(&self, name: &'static str) -> Result<usize, WcaError> {
+        self.header.iter().position(|column| column == name).ok_or_else(|| WcaError::MissingColumn(self.path.clone(), name))
+    }
+
+    // Rows after the header with their line numbers.
+    fn rows(&self) -> impl Iterator<Item = (usize, Vec<&str>)> {
+        self.contents.lines()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| (i + 1, line.trim_end_matches('\r').split('\t').collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve::Solve;
+    use crate::storage::test_dir;
+
+    fn export(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = test_dir(name);
+        for (file, contents) in files {
+            fs::write(dir.join(file), contents).unwrap();
+        }
+        dir
+    }
+
+    fn sample_export(name: &str) -> PathBuf {
+        export(name, &[
+            ("WCA_export_Countries.tsv", "id\tname\tcontinentId\tiso2\nUSA\tUnited States\t_North America\tUS\nGermany\tGermany\t_Europe\tDE\nFrance\tFrance\t_Europe\tFR\n"),
+            ("WCA_export_Persons.tsv", "id\tsubid\tname\tcountryId\tgender\n2010AAAA01\t1\tA\tUSA\tm\n2011BBBB01\t1\tB\tGermany\tf\n2011BBBB01\t2\tB\tUSA\tf\n2012CCCC01\t1\tC\tFrance\tm\n"),
+            ("WCA_export_RanksSingle.tsv", "personId\teventId\tbest\tworldRank\tcontinentRank\tcountryRank\n2010AAAA01\t333\t500\t1\t1\t1\n2011BBBB01\t333\t800\t2\t1\t1\n2012CCCC01\t333\t1000\t3\t2\t1\n2012CCCC01\t333fm\t25\t1\t1\t1\n"),
+            ("WCA_export_RanksAverage.tsv", "personId\teventId\tbest\tworldRank\tcontinentRank\tcountryRank\n2010AAAA01\t333\t700\t1\t1\t1\n2012CCCC01\t333\t1200\t2\t1\t1\n"),
+        ])
+    }
+
+    #[test]
+    fn ranks_results() {
+        let index = RankIndex::import(&sample_export("wca-export")).unwrap();
+        assert_eq!(index.event_count(), 2);
+
+        let ranks = index.ranks("333", Kind::Single, 900, Some("de")).unwrap();
+        assert_eq!(ranks.world, Position { rank: 3, of: 3 });
+        assert_eq!(ranks.continent, Some(Position { rank: 2, of: 2 }));
+        assert_eq!(ranks.country, Some(Position { rank: 2, of: 1 }));
+
+        // Ties share the rank of the tied result.
+        let ranks = index.ranks("333", Kind::Average, 700, None).unwrap();
+        assert_eq!(ranks.world, Position { rank: 1, of: 2 });
+        assert_eq!(ranks.country, None);
+
+        assert!(index.ranks("333fm", Kind::Average, 2500, None).is_none());
+
+        let records = index.records();
+        assert_eq!(records.get("333", Kind::Single), Some(500));
+        assert_eq!(records.get("333", Kind::Average), Some(700));
+        assert_eq!(records.get("333fm", Kind::Average), None);
+
+        let mut overrides = Records::default();
+        overrides.set("333", Kind::Average, 650);
+        let records = records.with_overrides(&overrides);
+        assert_eq!(records.get("333", Kind::Single), Some(500));
+        assert_eq!(records.get("333", Kind::Average), Some(650));
+        assert!(index.ranks("clock", Kind::Single, 500, None).is_none());
+    }
+
+    #[test]
+    fn index_round_trips_through_bytes() {
+        let index = RankIndex::import(&sample_export("wca-export-bytes")).unwrap();
+        let bytes = index.to_bytes();
+        let loaded = RankIndex::from_bytes(&bytes).unwrap();
+
+        assert_eq!(loaded.event_count(), 2);
+        assert_eq!(loaded.records(), index.records());
+        assert_eq!(loaded.country_id("fr"), Some("France"));
+        for (kind, result, country) in [(Kind::Single, 900, Some("de")), (Kind::Single, 400, Some("USA")), (Kind::Average, 1300, Some("FR"))] {
+            assert_eq!(loaded.ranks("333", kind, result, country), index.ranks("333", kind, result, country));
+        }
+
+        // Each result is stored once, not again for its continent and country.
+        assert!(bytes.len() < 150, "{} bytes", bytes.len());
+
+        assert!(matches!(RankIndex::from_bytes(b"{\"events\":{}}"), Err(WcaError::CorruptIndex)));
+        assert!(matches!(RankIndex::from_bytes(&bytes[..bytes.len() - 1]), Err(WcaError::CorruptIndex)));
+        let mut huge = INDEX_MAGIC.to_vec();
+        write_varint(&mut huge, u64::MAX);
+        assert!(matches!(RankIndex::from_bytes(&huge), Err(WcaError::CorruptIndex)));
+    }
+
+    #[test]
+    fn snake_case_export() {
+        let dir = export("wca-export-v2", &[
+            ("WCA_export_countries.tsv", "id\tname\tcontinent_id\tiso2\nUSA\tUnited States\t_North America\tUS\n"),
+            ("WCA_export_persons.tsv", "name\tgender\twca_id\tsub_id\tcountry_id\nA\tm\t2010AAAA01\t1\tUSA\n"),
+            ("WCA_export_ranks_single.tsv", "person_id\tevent_id\tbest\tworld_rank\tcontinent_rank\tcountry_rank\n2010AAAA01\t222\t150\t1\t1\t1\n"),
+            ("WCA_export_ranks_average.tsv", "person_id\tevent_id\tbest\tworld_rank\tcontinent_rank\tcountry_rank\n"),
+        ]);
+        let index = RankIndex::import(&dir).unwrap();
+        assert_eq!(index.country_id("united states"), None);
+        assert_eq!(index.country_id("usa"), Some("USA"));
+        assert_eq!(index.ranks("222", Kind::Single, 100, Some("US")).unwrap().country, Some(Position { rank: 1, of: 1 }));
+    }
+
+    #[test]
+    fn import_errors() {
+        let dir = export("wca-export-missing", &[("WCA_export_Countries.tsv", "id\tname\niso\tX\n")]);
+        assert!(matches!(RankIndex::import(&dir), Err(WcaError::MissingColumn(_, "continentid"))));
+
+        let dir = sample_export("wca-export-invalid");
+        fs::write(dir.join("WCA_export_RanksAverage.tsv"), "personId\teventId\tbest\n2010AAAA01\t333\tfast\n").unwrap();
+        assert!(matches!(RankIndex::import(&dir), Err(WcaError::InvalidRow(_, 2))));
+    }
+
+    #[test]
+    fn encodes_results() {
+        assert_eq!(encode_time(Duration::from_millis(12345)), 1234);
+        assert_eq!(encode_moves(24.333), 2433);
+        let result = MultiBldResult { solved: 9, attempted: 10 };
+        assert_eq!(encode_multi_bld(&result, Duration::from_secs(3512)), 910351201);
+        assert_eq!(format_result(Puzzle::MultiBld, Kind::Single, 910351201), "9/10 58:32.00");
+        assert_eq!(format_result(Puzzle::Cube3x3Fmc, Kind::Average, 2433), "24.33");
+        assert_eq!(format_result(Puzzle::Cube3x3, Kind::Single, 1234), "12.34");
+    }
+
+    #[test]
+    fn session_personal_bests() {
+        let solves = [12.0, 11.0, 14.0, 10.5, 13.0, 20.0]
+            .map(|s| Solve::build("R".to_owned(), Some(Duration::from_secs_f64(s)), None).unwrap());
+        assert_eq!(personal_bests(&Session::from_solves(solves.into())), [Some(1050), Some(1200)]);
+
+        let moves = [Some(30), Some(25), None, Some(28), Some(27), Some(26)]
+            .map(|moves| Solve::build_fmc("R".to_owned(), String::new(), moves));
+        let mut session = Session::new(Puzzle::Cube3x3Fmc);
+        for solve in moves {
+            session.save_solve(solve);
+        }
+        assert_eq!(personal_bests(&session), [Some(25), Some(2700)]);
+    }
+}